@@ -1,33 +1,50 @@
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use gpui::{App, Global, Hsla, PathBuilder, Pixels, Point, ReadGlobal, UpdateGlobal, Window, px};
+use gpui::{
+    App, Bounds, Corners, FillOptions, FillRule, Global, Hsla, Modifiers, MouseButton, PathBuilder,
+    PathStyle, Pixels, Point, ReadGlobal, RenderImage, UpdateGlobal, Window, point, px, size,
+};
+use serde::{Deserialize, Serialize};
 
-use crate::canvas_window_manager::CanvasWindowManager;
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Tool {
-    Cursor,
-    Pen,
-    Eraser,
-    Highlight,
-}
-
-impl Tool {
-    pub fn is_canvas_related(&self) -> bool {
-        *self == Self::Pen || *self == Self::Eraser
-    }
-}
+use crate::{
+    canvas_orchestrator::CanvasOrchestrator,
+    canvas_tool::{CanvasTool, Tool},
+    canvas_window_manager::CanvasWindowManager,
+    platform_impl::{self, PenState},
+    settings::{
+        HighlightSettings, HighlightStyle, MagnifierSettings, Settings, SpotlightSettings,
+        SpotlightShape,
+    },
+    shape_recognition,
+    sticker::StickerSource,
+};
 
 pub struct ToolState {
     tool: Tool,
     pub color: Hsla,
+    pub pen_width: f32,
+    /// Whether the freehand strokes roughly forming a shape are replaced with the clean shape.
+    pub recognize_shapes: bool,
+    pub stamp: StampKind,
+    pub sticker: Option<StickerSource>,
 }
 
 impl Global for ToolState {}
 
 impl ToolState {
-    pub fn register_global(cx: &mut App, tool: Tool, color: Hsla) {
-        cx.set_global(Self { color, tool });
+    pub fn register_global(cx: &mut App, tool: Tool, color: Hsla, pen_width: f32) {
+        cx.set_global(Self {
+            color,
+            tool,
+            pen_width,
+            recognize_shapes: false,
+            stamp: StampKind::Number,
+            sticker: StickerSource::list().into_iter().next(),
+        });
     }
 
     pub fn tool(&self) -> Tool {
@@ -40,10 +57,57 @@ impl ToolState {
         CanvasWindowManager::update_global(cx, |windows, cx| {
             let canvas_action_mode = !tool.is_canvas_related();
             windows.set_action_mode(cx, canvas_action_mode);
+            windows.update_wheel_listener(cx, tool);
+        });
+
+        // Remove the effect of the previous tool such as the spotlight.
+        CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+            orchestrator.notify_canvases(cx);
         });
     }
 }
 
+/// How the drag is turned into a stroke.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DrawMode {
+    Freehand,
+    /// The straight line snapped to 15 degrees.
+    Line,
+    Rectangle {
+        square: bool,
+    },
+    Ellipse {
+        circle: bool,
+    },
+    Erase,
+}
+
+impl DrawMode {
+    /// Choose the mode of the pen with the modifier keys. The right button erases.
+    ///
+    /// Shift draws a straight line, Ctrl a rectangle and Alt an ellipse.
+    /// Shift with Ctrl or Alt makes it a square or a circle.
+    pub fn new(button: MouseButton, modifiers: &Modifiers) -> Self {
+        if button == MouseButton::Right {
+            return Self::Erase;
+        }
+
+        if modifiers.control {
+            Self::Rectangle {
+                square: modifiers.shift,
+            }
+        } else if modifiers.alt {
+            Self::Ellipse {
+                circle: modifiers.shift,
+            }
+        } else if modifiers.shift {
+            Self::Line
+        } else {
+            Self::Freehand
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CanvasEraser {
     radius: Pixels,
@@ -61,24 +125,158 @@ impl CanvasEraser {
     fn draw(&mut self, pos: Point<Pixels>) {
         self.trail.push(pos);
     }
+
+    fn rescale(&mut self, scale_x: f32, scale_y: f32) {
+        rescale_trail(&mut self.trail, scale_x, scale_y);
+        // The larger scale keeps covering the strokes which were erased before.
+        self.radius *= scale_x.max(scale_y);
+    }
 }
 
-#[derive(Clone, Debug)]
+/// The point of a stroke with the state of the stylus when it is drawn.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct StrokePoint {
+    pub pos: Point<Pixels>,
+    /// From 0 to 1.
+    pub pressure: f32,
+    /// The tilt along the x and y axes from -1 to 1.
+    pub tilt: Option<(f32, f32)>,
+}
+
+impl StrokePoint {
+    /// The mouse is regarded as pressed with the half pressure like the pointer events of the web.
+    const MOUSE_PRESSURE: f32 = 0.5;
+    /// The stroke does not get thinner than this ratio even if the stylus is barely touching.
+    const MIN_WIDTH_RATIO: f32 = 0.2;
+
+    pub fn new(pos: Point<Pixels>, pen: Option<PenState>) -> Self {
+        match pen {
+            Some(pen) => Self {
+                pos,
+                pressure: pen.pressure,
+                tilt: pen.tilt,
+            },
+            None => Self {
+                pos,
+                pressure: Self::MOUSE_PRESSURE,
+                tilt: None,
+            },
+        }
+    }
+
+    fn width(&self, stroke: Pixels) -> f32 {
+        let ratio = (self.pressure / Self::MOUSE_PRESSURE).max(Self::MIN_WIDTH_RATIO);
+        // The tilted stylus draws a broader line like a marker.
+        let tilt = self.tilt.map_or(0., |(x, y)| x.hypot(y).min(1.));
+
+        f32::from(stroke) * ratio * (1. + tilt / 2.)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CanvasPath {
     color: Hsla,
     stroke: Pixels,
-    trail: Vec<Point<Pixels>>,
+    trail: Vec<StrokePoint>,
+    /// Whether the stroke is drawn as a shape with the modifier keys.
+    shaped: bool,
 }
 
 impl CanvasPath {
-    fn new(color: Hsla) -> Self {
-        let stroke = px(3.);
-
+    fn new(color: Hsla, stroke: Pixels, mode: DrawMode) -> Self {
         Self {
             color,
             stroke,
             trail: Vec::new(),
+            shaped: !matches!(mode, DrawMode::Freehand | DrawMode::Erase),
+        }
+    }
+
+    /// Get the clean shape if the freehand stroke roughly forms one.
+    fn recognize(&self) -> Option<Self> {
+        if self.shaped {
+            return None;
+        }
+
+        let trail: Vec<_> = self
+            .trail
+            .iter()
+            .map(|point| gpui::point(f32::from(point.pos.x), f32::from(point.pos.y)))
+            .collect();
+        let shape = shape_recognition::recognize(&trail)?;
+
+        let pressure =
+            self.trail.iter().map(|point| point.pressure).sum::<f32>() / self.trail.len() as f32;
+        let trail = shape
+            .points()
+            .into_iter()
+            .map(|pos| StrokePoint {
+                pos: point(px(pos.x), px(pos.y)),
+                pressure,
+                tilt: None,
+            })
+            .collect();
+
+        Some(Self {
+            trail,
+            shaped: true,
+            ..self.clone()
+        })
+    }
+
+    /// Get the outline of the stroke, whose width changes with the pressure, with the round caps.
+    fn outline(&self) -> Vec<Point<f32>> {
+        const CAP_SEGMENTS: usize = 8;
+
+        let pos = |i: usize| {
+            let pos = self.trail[i.min(self.trail.len() - 1)].pos;
+            point(f32::from(pos.x), f32::from(pos.y))
+        };
+
+        let mut left = Vec::with_capacity(self.trail.len());
+        let mut right = Vec::with_capacity(self.trail.len());
+        let mut angles = Vec::with_capacity(self.trail.len());
+
+        for (i, stroke_point) in self.trail.iter().enumerate() {
+            let center = pos(i);
+            let direction = pos(i + 1) - pos(i.saturating_sub(1));
+            // The left side of the direction, which is the right side on the screen.
+            let angle = if direction == point(0., 0.) {
+                std::f32::consts::FRAC_PI_2
+            } else {
+                direction.y.atan2(direction.x) + std::f32::consts::FRAC_PI_2
+            };
+            // Keep the width at the corners such as the ones of the rectangles.
+            let turn = turn_angle(pos(i) - pos(i.saturating_sub(1)), pos(i + 1) - pos(i));
+            let r = stroke_point.width(self.stroke) / 2. / (turn / 2.).cos().max(0.5);
+            let normal = point(angle.cos() * r, angle.sin() * r);
+
+            left.push(center + normal);
+            right.push(center - normal);
+            angles.push((angle, r));
         }
+
+        // The semicircle from the left side to the right side around the end of the stroke.
+        let cap = |center: Point<f32>, (angle, r): (f32, f32), reversed: bool| {
+            let start = if reversed {
+                angle + std::f32::consts::PI
+            } else {
+                angle
+            };
+
+            (1..CAP_SEGMENTS).map(move |k| {
+                let angle = start - std::f32::consts::PI * k as f32 / CAP_SEGMENTS as f32;
+                center + point(angle.cos() * r, angle.sin() * r)
+            })
+        };
+
+        let last = self.trail.len() - 1;
+        let mut outline = left;
+        outline.extend(cap(pos(last), angles[last], false));
+        outline.extend(right.into_iter().rev());
+        outline.extend(cap(pos(0), angles[0], true));
+
+        outline
     }
 
     fn paint(&self, window: &mut Window) {
@@ -86,19 +284,101 @@ impl CanvasPath {
             return;
         }
 
-        let mut path = PathBuilder::stroke(self.stroke);
-        let mut iter = self.trail.iter().cloned();
-        path.move_to(iter.next().unwrap());
+        // The outline may cross itself at sharp turns, which the even-odd rule would leave empty.
+        let fill = FillOptions::default().with_fill_rule(FillRule::NonZero);
+        let mut path = PathBuilder::fill().with_style(PathStyle::Fill(fill));
+        let outline: Vec<_> = self
+            .outline()
+            .into_iter()
+            .map(|pos| point(px(pos.x), px(pos.y)))
+            .collect();
+        path.add_polygon(&outline, true);
+
+        window.paint_path(path.build().unwrap(), self.color);
+    }
 
-        for pos in iter {
-            path.line_to(pos);
+    /// Draw the stroke onto the image, e.g. to save the annotated screen.
+    pub fn rasterize(&self, pixmap: &mut tiny_skia::Pixmap, transform: tiny_skia::Transform) {
+        if self.trail.is_empty() {
+            return;
         }
 
-        window.paint_path(path.build().unwrap(), self.color);
+        let mut builder = tiny_skia::PathBuilder::new();
+        let outline = self.outline();
+
+        builder.move_to(outline[0].x, outline[0].y);
+        for pos in &outline[1..] {
+            builder.line_to(pos.x, pos.y);
+        }
+        builder.close();
+
+        let Some(path) = builder.finish() else {
+            return;
+        };
+
+        let color = gpui::Rgba::from(self.color);
+        let mut paint = tiny_skia::Paint::default();
+        paint.set_color_rgba8(
+            (color.r * 255.) as u8,
+            (color.g * 255.) as u8,
+            (color.b * 255.) as u8,
+            (color.a * 255.) as u8,
+        );
+        paint.anti_alias = true;
+
+        pixmap.fill_path(&path, &paint, tiny_skia::FillRule::Winding, transform, None);
     }
 
-    fn draw(&mut self, pos: Point<Pixels>) {
-        self.trail.push(pos);
+    fn draw(&mut self, point: StrokePoint, mode: DrawMode) {
+        let Some(start) = self.trail.first().map(|first| first.pos) else {
+            self.trail.push(point);
+            return;
+        };
+        let end = point.pos;
+        let at = |pos| StrokePoint { pos, ..point };
+
+        // The shapes are made again from the start point at every move.
+        self.trail = match mode {
+            DrawMode::Freehand | DrawMode::Erase => {
+                self.trail.push(point);
+                return;
+            }
+            DrawMode::Line => vec![at(start), at(snap_line(start, end))],
+            DrawMode::Rectangle { square } => {
+                let end = constrain_aspect(start, end, square);
+
+                [
+                    start,
+                    gpui::point(end.x, start.y),
+                    end,
+                    gpui::point(start.x, end.y),
+                    start,
+                ]
+                .map(at)
+                .into()
+            }
+            DrawMode::Ellipse { circle } => {
+                const SEGMENTS: usize = 64;
+
+                let end = constrain_aspect(start, end, circle);
+                let center = (start + end) / 2.;
+                let (rx, ry) = ((end.x - start.x) / 2., (end.y - start.y) / 2.);
+
+                (0..=SEGMENTS)
+                    .map(|i| {
+                        let angle = std::f32::consts::TAU * i as f32 / SEGMENTS as f32;
+                        at(center + gpui::point(rx * angle.cos(), ry * angle.sin()))
+                    })
+                    .collect()
+            }
+        };
+    }
+
+    fn rescale(&mut self, scale_x: f32, scale_y: f32) {
+        for point in &mut self.trail {
+            point.pos.x *= scale_x;
+            point.pos.y *= scale_y;
+        }
     }
 
     fn erase(&self, eraser_trail: &[Point<Pixels>], radius: Pixels) -> Option<Vec<Self>> {
@@ -111,8 +391,8 @@ impl CanvasPath {
         for point in &self.trail {
             let mut hit = false;
             for e_pos in eraser_trail {
-                let dx = f32::from(point.x - e_pos.x);
-                let dy = f32::from(point.y - e_pos.y);
+                let dx = f32::from(point.pos.x - e_pos.x);
+                let dy = f32::from(point.pos.y - e_pos.y);
                 if dx * dx + dy * dy <= radius_sq {
                     hit = true;
                     break;
@@ -124,9 +404,8 @@ impl CanvasPath {
 
                 if !current_trail.is_empty() {
                     new_paths.push(Self {
-                        color: self.color,
-                        stroke: self.stroke,
                         trail: current_trail,
+                        ..self.clone()
                     });
                     current_trail = Vec::new();
                 }
@@ -141,9 +420,8 @@ impl CanvasPath {
 
         if !current_trail.is_empty() {
             new_paths.push(Self {
-                color: self.color,
-                stroke: self.stroke,
                 trail: current_trail,
+                ..self.clone()
             });
         }
 
@@ -151,17 +429,485 @@ impl CanvasPath {
     }
 }
 
+/// Get the angle which the direction turns from `a` to `b`.
+fn turn_angle(a: Point<f32>, b: Point<f32>) -> f32 {
+    if a == point(0., 0.) || b == point(0., 0.) {
+        return 0.;
+    }
+
+    (a.x * b.y - a.y * b.x).atan2(a.x * b.x + a.y * b.y)
+}
+
+/// Move `end` so that the line from `start` is at a multiple of 15 degrees.
+fn snap_line(start: Point<Pixels>, end: Point<Pixels>) -> Point<Pixels> {
+    const STEP: f32 = std::f32::consts::PI / 12.;
+
+    let (dx, dy) = (f32::from(end.x - start.x), f32::from(end.y - start.y));
+    let angle = (dy.atan2(dx) / STEP).round() * STEP;
+    let length = dx.hypot(dy);
+
+    start + point(px(angle.cos() * length), px(angle.sin() * length))
+}
+
+/// Move `end` so that the box from `start` becomes a square if `square` is true.
+fn constrain_aspect(start: Point<Pixels>, end: Point<Pixels>, square: bool) -> Point<Pixels> {
+    if !square {
+        return end;
+    }
+
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let side = dx.abs().max(dy.abs());
+
+    start + point(side * dx.signum(), side * dy.signum())
+}
+
+/// Paint the cursor highlight centered at `pos`.
+pub fn paint_highlight(window: &mut Window, pos: Point<Pixels>, highlight: &HighlightSettings) {
+    const RING_WIDTH: Pixels = px(3.);
+
+    let mut path = match highlight.style {
+        HighlightStyle::Filled => PathBuilder::fill(),
+        HighlightStyle::Ring => PathBuilder::stroke(RING_WIDTH),
+    };
+    add_circle(&mut path, pos, px(highlight.radius));
+
+    let color = highlight.color.alpha(highlight.opacity);
+    window.paint_path(path.build().unwrap(), color)
+}
+
+/// Dim the whole window except the area around `pos`.
+/// If `pos` is `None`, the whole window is dimmed.
+pub fn paint_spotlight(
+    window: &mut Window,
+    pos: Option<Point<Pixels>>,
+    spotlight: &SpotlightSettings,
+) {
+    const RECTANGLE_ASPECT_RATIO: f32 = 1.6;
+
+    let size = window.viewport_size();
+    let fill = FillOptions::default().with_fill_rule(FillRule::EvenOdd);
+    let mut path = PathBuilder::fill().with_style(PathStyle::Fill(fill));
+
+    path.add_polygon(
+        &[
+            point(px(0.), px(0.)),
+            point(size.width, px(0.)),
+            point(size.width, size.height),
+            point(px(0.), size.height),
+        ],
+        true,
+    );
+
+    // The even-odd rule makes a hole in the dimmed area.
+    if let Some(pos) = pos {
+        let r = px(spotlight.radius);
+
+        match spotlight.shape {
+            SpotlightShape::Circle => add_circle(&mut path, pos, r),
+            SpotlightShape::Rectangle => {
+                let half_width = r * RECTANGLE_ASPECT_RATIO;
+
+                path.add_polygon(
+                    &[
+                        point(pos.x - half_width, pos.y - r),
+                        point(pos.x + half_width, pos.y - r),
+                        point(pos.x + half_width, pos.y + r),
+                        point(pos.x - half_width, pos.y + r),
+                    ],
+                    true,
+                );
+            }
+        }
+    }
+
+    window.paint_path(
+        path.build().unwrap(),
+        gpui::black().alpha(spotlight.opacity),
+    )
+}
+
+/// How often the screen under the magnifier lens is captured again.
+/// The lens shows the last capture in between, so that it does not capture on every frame.
+const LENS_CAPTURE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The screen captured around the cursor for the magnifier lens.
+struct LensCapture {
+    /// The captured size, so that the resized lens is captured again at once.
+    size: dpi::LogicalSize<f64>,
+    captured_at: Instant,
+    image: Arc<RenderImage>,
+}
+
+/// Get the area of the desktop which the lens at `pos` magnifies.
+fn lens_source(
+    window: &Window,
+    pos: Point<Pixels>,
+    magnifier: &MagnifierSettings,
+) -> (dpi::LogicalPosition<f64>, dpi::LogicalSize<f64>) {
+    let source_r = magnifier.radius / magnifier.zoom;
+    let viewport = window.viewport_size();
+
+    // Keep the captured area inside the window.
+    let center_x = f32::from(pos.x)
+        .min(f32::from(viewport.width) - source_r)
+        .max(source_r);
+    let center_y = f32::from(pos.y)
+        .min(f32::from(viewport.height) - source_r)
+        .max(source_r);
+
+    let window_origin = window.bounds().origin;
+    let origin = dpi::LogicalPosition::new(
+        f32::from(window_origin.x) + center_x - source_r,
+        f32::from(window_origin.y) + center_y - source_r,
+    );
+    let source_size = dpi::LogicalSize::new(source_r * 2., source_r * 2.);
+
+    (origin.cast(), source_size.cast())
+}
+
+fn capture_lens(
+    origin: dpi::LogicalPosition<f64>,
+    size: dpi::LogicalSize<f64>,
+    scale_factor: f64,
+) -> Option<LensCapture> {
+    static ERROR_REPORTED: std::sync::atomic::AtomicBool =
+        std::sync::atomic::AtomicBool::new(false);
+
+    let captured = match platform_impl::capture_screen(origin, size, scale_factor) {
+        Ok(captured) => captured,
+        Err(error) => {
+            // The capture is tried again and again while the lens is shown, so we report it only once.
+            if !ERROR_REPORTED.swap(true, std::sync::atomic::Ordering::Relaxed) {
+                eprintln!("Failed to capture the screen: {error:#}");
+            }
+
+            return None;
+        }
+    };
+
+    // The buffer keeps the captured pixels in BGRA order, which `RenderImage` expects,
+    // although `RgbaImage` names the channels in RGBA order.
+    let buffer = image::RgbaImage::from_raw(captured.width, captured.height, captured.data)?;
+
+    Some(LensCapture {
+        size,
+        captured_at: Instant::now(),
+        image: Arc::new(RenderImage::new(vec![image::Frame::new(buffer)])),
+    })
+}
+
+/// Paint the lens which shows the captured `image` of the screen around `pos`.
+fn paint_magnifier(
+    window: &mut Window,
+    pos: Point<Pixels>,
+    magnifier: &MagnifierSettings,
+    image: Arc<RenderImage>,
+) {
+    let r = magnifier.radius;
+    let source_r = r / magnifier.zoom;
+    let viewport = window.viewport_size();
+
+    // Place the lens beside the cursor, so that the captured area does not include the lens itself.
+    let distance = px(r / std::f32::consts::SQRT_2 + source_r + 4.);
+    let mut lens_center = pos + point(distance, distance);
+    if lens_center.x + px(r) > viewport.width {
+        lens_center.x = pos.x - distance;
+    }
+    if lens_center.y + px(r) > viewport.height {
+        lens_center.y = pos.y - distance;
+    }
+
+    let bounds = Bounds::new(
+        lens_center - point(px(r), px(r)),
+        size(px(r * 2.), px(r * 2.)),
+    );
+    _ = window.paint_image(bounds, Corners::all(px(r)), image, 0, false);
+
+    let mut border = PathBuilder::stroke(px(2.));
+    add_circle(&mut border, lens_center, px(r));
+    window.paint_path(border.build().unwrap(), gpui::white().alpha(0.8));
+}
+
+fn add_circle(path: &mut PathBuilder, center: Point<Pixels>, r: Pixels) {
+    let cx = center.x;
+    let cy = center.y;
+
+    path.move_to(Point::new(cx + r, cy));
+    path.arc_to(
+        Point::new(r, r),
+        px(0.0),
+        false,
+        false,
+        Point::new(cx - r, cy),
+    );
+    path.arc_to(
+        Point::new(r, r),
+        px(0.0),
+        false,
+        false,
+        Point::new(cx + r, cy),
+    );
+    path.close();
+}
+
+fn rescale_trail(trail: &mut [Point<Pixels>], scale_x: f32, scale_y: f32) {
+    for pos in trail {
+        pos.x *= scale_x;
+        pos.y *= scale_y;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StampKind {
+    /// The badge with the number counting up from 1 on each canvas.
+    Number,
+    Check,
+    Cross,
+    /// The arrow pointing at the clicked point from the upper left.
+    Arrow,
+}
+
+#[derive(Clone, Debug)]
+pub struct CanvasStamp {
+    kind: StampKind,
+    /// The number on the badge of `StampKind::Number`.
+    number: usize,
+    pos: Point<Pixels>,
+    color: Hsla,
+}
+
+impl CanvasStamp {
+    const RADIUS: f32 = 14.;
+
+    /// Get the strokes to draw the stamp,
+    /// so that it is painted, erased and captured in the same way as the strokes.
+    fn paths(&self) -> Vec<CanvasPath> {
+        let r = Self::RADIUS;
+        let path = |color, stroke, trail: &[Point<f32>]| CanvasPath {
+            color,
+            stroke: px(stroke),
+            trail: trail
+                .iter()
+                .map(|pos| StrokePoint {
+                    pos: self.pos + point(px(pos.x), px(pos.y)),
+                    pressure: StrokePoint::MOUSE_PRESSURE,
+                    tilt: None,
+                })
+                .collect(),
+            shaped: true,
+        };
+
+        match self.kind {
+            StampKind::Number => {
+                // The round cap of a dot makes the badge.
+                let mut paths = vec![path(self.color, r * 2., &[point(0., 0.)])];
+                paths.extend(
+                    digit_strokes(self.number, r)
+                        .iter()
+                        .map(|trail| path(gpui::white(), 2.5, trail)),
+                );
+
+                paths
+            }
+            StampKind::Check => vec![path(
+                self.color,
+                5.,
+                &[point(-r, 0.), point(-r / 3., r * 0.8), point(r, -r * 0.8)],
+            )],
+            StampKind::Cross => vec![
+                path(self.color, 5., &[point(-r, -r), point(r, r)]),
+                path(self.color, 5., &[point(r, -r), point(-r, r)]),
+            ],
+            StampKind::Arrow => {
+                let arrow = shape_recognition::Shape::Arrow {
+                    start: point(-r * 3., -r * 3.),
+                    end: point(0., 0.),
+                };
+
+                vec![path(self.color, 4., &arrow.points())]
+            }
+        }
+    }
+
+    fn rescale(&mut self, scale_x: f32, scale_y: f32) {
+        self.pos.x *= scale_x;
+        self.pos.y *= scale_y;
+    }
+}
+
+/// Get the strokes of the digits of `number` centered in the badge with the radius `r`.
+/// The digits are drawn with the strokes instead of a font, so that they can be captured too.
+fn digit_strokes(number: usize, r: f32) -> Vec<Vec<Point<f32>>> {
+    // The digits are in the box from (0, 0) to (1, 2).
+    const DIGITS: [&[&[(f32, f32)]]; 10] = [
+        &[&[(0., 0.), (1., 0.), (1., 2.), (0., 2.), (0., 0.)]],
+        &[&[(0.2, 0.4), (0.6, 0.), (0.6, 2.)]],
+        &[&[(0., 0.), (1., 0.), (1., 1.), (0., 1.), (0., 2.), (1., 2.)]],
+        &[
+            &[(0., 0.), (1., 0.), (1., 2.), (0., 2.)],
+            &[(0., 1.), (1., 1.)],
+        ],
+        &[&[(0., 0.), (0., 1.), (1., 1.)], &[(1., 0.), (1., 2.)]],
+        &[&[(1., 0.), (0., 0.), (0., 1.), (1., 1.), (1., 2.), (0., 2.)]],
+        &[&[(1., 0.), (0., 0.), (0., 2.), (1., 2.), (1., 1.), (0., 1.)]],
+        &[&[(0., 0.), (1., 0.), (1., 2.)]],
+        &[
+            &[(0., 0.), (1., 0.), (1., 2.), (0., 2.), (0., 0.)],
+            &[(0., 1.), (1., 1.)],
+        ],
+        &[&[(1., 1.), (0., 1.), (0., 0.), (1., 0.), (1., 2.), (0., 2.)]],
+    ];
+    const SPACING: f32 = 0.6;
+
+    let digits: Vec<_> = number
+        .to_string()
+        .bytes()
+        .map(|digit| DIGITS[(digit - b'0') as usize])
+        .collect();
+
+    // Fit the digits in the badge.
+    let width = digits.len() as f32 * (1. + SPACING) - SPACING;
+    let scale = (r * 0.5).min(r * 1.2 / width);
+    let origin = point(-width * scale / 2., -scale);
+
+    digits
+        .iter()
+        .enumerate()
+        .flat_map(|(i, strokes)| {
+            let offset = i as f32 * (1. + SPACING);
+
+            strokes.iter().map(move |stroke| {
+                stroke
+                    .iter()
+                    .map(|(x, y)| origin + point((x + offset) * scale, y * scale))
+                    .collect()
+            })
+        })
+        .collect()
+}
+
+/// The image put on the canvas, which can be moved and scaled after it is put.
+#[derive(Clone, Debug)]
+pub struct CanvasSticker {
+    /// The identifier to find the sticker moved by `CanvasAction::MoveSticker`.
+    id: usize,
+    pub source: StickerSource,
+    pub image: Arc<RenderImage>,
+    pub bounds: Bounds<Pixels>,
+}
+
+impl CanvasSticker {
+    /// The maximum length of the longer side when the sticker is put.
+    const MAX_SIZE: f32 = 160.;
+    const MIN_SIZE: f32 = 16.;
+
+    fn is_erased(&self, eraser: &CanvasEraser) -> bool {
+        eraser
+            .trail
+            .iter()
+            .any(|pos| self.bounds.dilate(eraser.radius).contains(pos))
+    }
+
+    fn rescale(bounds: &mut Bounds<Pixels>, scale_x: f32, scale_y: f32) {
+        bounds.origin.x *= scale_x;
+        bounds.origin.y *= scale_y;
+        bounds.size.width *= scale_x;
+        bounds.size.height *= scale_y;
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum CanvasAction {
     Clear,
     DrawLine(CanvasPath),
     Erase(CanvasEraser),
+    /// Replace the stroke drawn just before with the clean shape.
+    Recognize(CanvasPath),
+    Stamp(CanvasStamp),
+    Sticker(CanvasSticker),
+    /// Move or scale the sticker put before.
+    MoveSticker {
+        id: usize,
+        bounds: Bounds<Pixels>,
+    },
+}
+
+impl CanvasAction {
+    /// The name shown in the history, e.g. `Pen stroke`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Clear => "Clear",
+            Self::DrawLine(path) if path.shaped => "Shape",
+            Self::DrawLine(_) => "Pen stroke",
+            Self::Erase(_) => "Erase",
+            Self::Recognize(_) => "Shape recognition",
+            Self::Stamp(_) => "Stamp",
+            Self::Sticker(_) => "Sticker",
+            Self::MoveSticker { .. } => "Move sticker",
+        }
+    }
+}
+
+/// The sticker being dragged, which is moved as an action when it is released.
+struct GrabbedSticker {
+    id: usize,
+    /// The offset of the cursor from the origin of the sticker.
+    offset: Point<Pixels>,
+    /// Where the sticker is when it is grabbed.
+    from: Bounds<Pixels>,
+    bounds: Bounds<Pixels>,
+}
+
+/// The ring expanding from the point where the mouse is clicked.
+struct Ripple {
+    pos: Point<Pixels>,
+    color: Hsla,
+    started_at: Instant,
+}
+
+impl Ripple {
+    const DURATION: Duration = Duration::from_millis(500);
+    const MIN_RADIUS: f32 = 6.;
+    const MAX_RADIUS: f32 = 36.;
+
+    fn is_finished(&self) -> bool {
+        self.started_at.elapsed() >= Self::DURATION
+    }
+
+    fn paint(&self, window: &mut Window) {
+        let progress = self.started_at.elapsed().as_secs_f32() / Self::DURATION.as_secs_f32();
+        let progress = progress.min(1.);
+        let radius = Self::MIN_RADIUS + (Self::MAX_RADIUS - Self::MIN_RADIUS) * progress;
+
+        let mut path = PathBuilder::stroke(px(3.));
+        add_circle(&mut path, self.pos, px(radius));
+
+        let color = self.color.alpha(self.color.a * (1. - progress));
+        window.paint_path(path.build().unwrap(), color);
+    }
 }
 
 pub struct Canvas {
     stack: VecDeque<CanvasAction>,
+    /// The undone actions which can be redone until a new action is added.
+    undone: Vec<CanvasAction>,
     painting: bool,
+    /// The mode of the stroke being painted, which is chosen when it starts.
+    draw_mode: DrawMode,
+    /// Whether the strokes and the stickers are hidden without being cleared.
+    hidden: bool,
     highlight_pos: Option<Point<Pixels>>,
+    ripples: Vec<Ripple>,
+    /// The screen captured for the magnifier lens.
+    /// It should be removed from the sprite atlas when it is no longer shown.
+    lens: Option<LensCapture>,
+    /// Whether the lens is painted in the frame being painted.
+    lens_painted: bool,
+    next_sticker_id: usize,
+    grabbed_sticker: Option<GrabbedSticker>,
+    /// When the sticker is scaled with the mouse wheel last time.
+    /// The scrolls in a row are merged into one action.
+    last_sticker_scroll: Option<Instant>,
 }
 
 impl Canvas {
@@ -170,13 +916,79 @@ impl Canvas {
     pub fn new() -> Self {
         Self {
             stack: VecDeque::new(),
+            undone: Vec::new(),
             painting: false,
+            draw_mode: DrawMode::Freehand,
+            hidden: false,
             highlight_pos: None,
+            ripples: Vec::new(),
+            lens: None,
+            lens_painted: false,
+            next_sticker_id: 0,
+            grabbed_sticker: None,
+            last_sticker_scroll: None,
+        }
+    }
+
+    pub fn paint(&mut self, window: &mut Window, tool: &dyn CanvasTool, settings: &Settings) {
+        if !self.hidden {
+            // Stickers below the strokes, so that they can be annotated.
+            for sticker in self.visible_stickers() {
+                _ = window.paint_image(sticker.bounds, Corners::default(), sticker.image, 0, false);
+            }
+
+            // Normal user drawings
+            for path in self.visible_paths() {
+                path.paint(window);
+            }
+        }
+
+        // Click ripples
+        self.ripples.retain(|ripple| !ripple.is_finished());
+        for ripple in &self.ripples {
+            ripple.paint(window);
+        }
+
+        // The preview of the tool, such as the cursor highlight
+        self.lens_painted = false;
+        tool.paint_preview(self, window, settings);
+
+        if !self.lens_painted
+            && let Some(lens) = self.lens.take()
+        {
+            _ = window.drop_image(lens.image);
+        }
+    }
+
+    /// Paint the magnifier lens at `pos`. The screen is captured again only when
+    /// the last capture is older than `LENS_CAPTURE_INTERVAL` or the lens is resized.
+    pub fn paint_lens(
+        &mut self,
+        window: &mut Window,
+        pos: Point<Pixels>,
+        magnifier: &MagnifierSettings,
+    ) {
+        let (origin, size) = lens_source(window, pos, magnifier);
+
+        let is_fresh = self.lens.as_ref().is_some_and(|lens| {
+            lens.size == size && lens.captured_at.elapsed() < LENS_CAPTURE_INTERVAL
+        });
+        if !is_fresh
+            && let Some(capture) = capture_lens(origin, size, window.scale_factor() as f64)
+            && let Some(old) = self.lens.replace(capture)
+        {
+            _ = window.drop_image(old.image);
+        }
+
+        // Keep showing the last capture if capturing fails for a moment.
+        if let Some(lens) = &self.lens {
+            paint_magnifier(window, pos, magnifier, lens.image.clone());
+            self.lens_painted = true;
         }
     }
 
-    pub fn paint(&mut self, window: &mut Window) {
-        // Normal user drawings
+    /// Get the actions after the last clear.
+    fn visible_actions(&self) -> impl Iterator<Item = &CanvasAction> {
         let start_index = self
             .stack
             .iter()
@@ -184,8 +996,13 @@ impl Canvas {
             .map(|i| i + 1)
             .unwrap_or(0);
 
+        self.stack.iter().skip(start_index)
+    }
+
+    /// Get the strokes which are not cleared or erased.
+    pub fn visible_paths(&self) -> Vec<CanvasPath> {
         let mut visible_paths: Vec<CanvasPath> = Vec::new();
-        for action in self.stack.iter().skip(start_index) {
+        for action in self.visible_actions() {
             match action {
                 CanvasAction::DrawLine(path) => visible_paths.push(path.clone()),
                 CanvasAction::Erase(eraser) => {
@@ -200,86 +1017,308 @@ impl Canvas {
 
                     visible_paths = next_paths;
                 }
+                CanvasAction::Recognize(shape) => {
+                    visible_paths.pop();
+                    visible_paths.push(shape.clone());
+                }
+                CanvasAction::Stamp(stamp) => visible_paths.extend(stamp.paths()),
                 CanvasAction::Clear => visible_paths.clear(),
+                CanvasAction::Sticker(_) | CanvasAction::MoveSticker { .. } => {}
             }
         }
 
-        for path in visible_paths {
-            path.paint(window);
-        }
-
-        // Cursor highlight
-        if let Some(pos) = self.highlight_pos.take() {
-            let cx = pos.x;
-            let cy = pos.y;
-            let r = px(20.0);
-            let mut path = PathBuilder::fill();
+        visible_paths
+    }
 
-            path.move_to(Point::new(cx + r, cy));
-            path.arc_to(
-                Point::new(r, r),
-                px(0.0),
-                false,
-                false,
-                Point::new(cx - r, cy),
-            );
-            path.arc_to(
-                Point::new(r, r),
-                px(0.0),
-                false,
-                false,
-                Point::new(cx + r, cy),
-            );
+    /// Get the stickers which are not cleared or erased, at the positions where they are moved.
+    pub fn visible_stickers(&self) -> Vec<CanvasSticker> {
+        let mut visible_stickers: Vec<CanvasSticker> = Vec::new();
+        for action in self.visible_actions() {
+            match action {
+                CanvasAction::Sticker(sticker) => visible_stickers.push(sticker.clone()),
+                CanvasAction::MoveSticker { id, bounds } => {
+                    if let Some(sticker) = visible_stickers.iter_mut().find(|s| s.id == *id) {
+                        sticker.bounds = *bounds;
+                    }
+                }
+                CanvasAction::Erase(eraser) => {
+                    visible_stickers.retain(|sticker| !sticker.is_erased(eraser));
+                }
+                _ => {}
+            }
+        }
 
-            window.paint_path(path.build().unwrap(), gpui::red().alpha(0.4))
+        if let Some(grabbed) = &self.grabbed_sticker
+            && let Some(sticker) = visible_stickers.iter_mut().find(|s| s.id == grabbed.id)
+        {
+            sticker.bounds = grabbed.bounds;
         }
+
+        visible_stickers
     }
 
-    pub fn draw(&mut self, cx: &App, pos: Point<Pixels>) {
+    /// Add the point to the stroke being painted, or start a new stroke with `mode`.
+    /// The mode is kept until the stroke ends, so pressing or releasing a modifier key
+    /// in the middle of the drag does not turn the stroke into another shape.
+    pub fn draw(&mut self, cx: &App, point: StrokePoint, mode: DrawMode) {
         if !self.painting {
             self.painting = true;
-            let state = ToolState::global(cx);
+            self.draw_mode = mode;
 
-            if state.tool == Tool::Eraser {
-                let mut eraser = CanvasEraser::new(px(20.));
-                eraser.draw(pos);
+            if mode == DrawMode::Erase {
+                let radius = Settings::global(cx).tools.eraser_radius;
+                let mut eraser = CanvasEraser::new(px(radius));
+                eraser.draw(point.pos);
                 self.push_action(CanvasAction::Erase(eraser));
             } else {
-                let color = state.color;
-                let mut path = CanvasPath::new(color);
-                path.draw(pos);
+                let state = ToolState::global(cx);
+                let mut path = CanvasPath::new(state.color, px(state.pen_width), mode);
+                path.draw(point, mode);
                 self.push_action(CanvasAction::DrawLine(path));
             }
         } else {
+            let mode = self.draw_mode;
             let maybe_action = self.stack.back_mut().unwrap();
 
             match maybe_action {
-                CanvasAction::DrawLine(path) => path.draw(pos),
-                CanvasAction::Erase(eraser) => eraser.draw(pos),
+                CanvasAction::DrawLine(path) => path.draw(point, mode),
+                CanvasAction::Erase(eraser) => eraser.draw(point.pos),
                 _ => {}
             }
         }
     }
 
+    /// Put the stamp chosen in the tool state at `pos`.
+    pub fn stamp(&mut self, cx: &App, pos: Point<Pixels>) {
+        let state = ToolState::global(cx);
+        self.put_stamp(state.stamp, pos, state.color);
+    }
+
+    fn put_stamp(&mut self, kind: StampKind, pos: Point<Pixels>, color: Hsla) {
+        // Count the numbered stamps after the last clear,
+        // so that clearing resets the number and undoing decreases it.
+        let number = self
+            .stack
+            .iter()
+            .rev()
+            .take_while(|action| !matches!(action, CanvasAction::Clear))
+            .filter(|action| {
+                matches!(action, CanvasAction::Stamp(stamp) if stamp.kind == StampKind::Number)
+            })
+            .count()
+            + 1;
+
+        self.painting = false;
+        self.push_action(CanvasAction::Stamp(CanvasStamp {
+            kind,
+            number,
+            pos,
+            color,
+        }));
+    }
+
+    /// Put the sticker centered at `pos`.
+    /// The large image is shrunk to `CanvasSticker::MAX_SIZE` keeping its aspect ratio.
+    pub fn put_sticker(
+        &mut self,
+        source: StickerSource,
+        image: Arc<RenderImage>,
+        pos: Point<Pixels>,
+    ) {
+        let image_size = image.size(0);
+        let (width, height) = (image_size.width.0 as f32, image_size.height.0 as f32);
+        let scale = (CanvasSticker::MAX_SIZE / width.max(height)).min(1.);
+        let bounds = Bounds::centered_at(pos, size(px(width * scale), px(height * scale)));
+
+        self.push_sticker(source, image, bounds);
+    }
+
+    fn push_sticker(
+        &mut self,
+        source: StickerSource,
+        image: Arc<RenderImage>,
+        bounds: Bounds<Pixels>,
+    ) {
+        let id = self.next_sticker_id;
+        self.next_sticker_id += 1;
+
+        self.painting = false;
+        self.push_action(CanvasAction::Sticker(CanvasSticker {
+            id,
+            source,
+            image,
+            bounds,
+        }));
+    }
+
+    /// Put the stroke loaded from the session file.
+    pub fn restore_path(&mut self, path: CanvasPath) {
+        self.push_action(CanvasAction::DrawLine(path));
+    }
+
+    /// Put the sticker loaded from the session file.
+    pub fn restore_sticker(
+        &mut self,
+        source: StickerSource,
+        image: Arc<RenderImage>,
+        bounds: Bounds<Pixels>,
+    ) {
+        self.push_sticker(source, image, bounds);
+    }
+
+    /// Get the topmost sticker at `pos`.
+    fn sticker_at(&self, pos: Point<Pixels>) -> Option<CanvasSticker> {
+        self.visible_stickers()
+            .into_iter()
+            .rev()
+            .find(|sticker| sticker.bounds.contains(&pos))
+    }
+
+    /// Start dragging the sticker at `pos`. Returns whether there is the sticker.
+    pub fn grab_sticker(&mut self, pos: Point<Pixels>) -> bool {
+        let Some(sticker) = self.sticker_at(pos) else {
+            return false;
+        };
+
+        self.painting = false;
+        self.grabbed_sticker = Some(GrabbedSticker {
+            id: sticker.id,
+            offset: pos - sticker.bounds.origin,
+            from: sticker.bounds,
+            bounds: sticker.bounds,
+        });
+
+        true
+    }
+
+    pub fn drag_sticker(&mut self, pos: Point<Pixels>) {
+        if let Some(grabbed) = &mut self.grabbed_sticker {
+            grabbed.bounds.origin = pos - grabbed.offset;
+        }
+    }
+
+    /// Drop the sticker being dragged. Returns the number of the actions added,
+    /// which is 0 if the sticker is not moved nor scaled.
+    pub fn release_sticker(&mut self) -> usize {
+        match self.grabbed_sticker.take() {
+            Some(grabbed) if grabbed.bounds != grabbed.from => {
+                self.push_action(CanvasAction::MoveSticker {
+                    id: grabbed.id,
+                    bounds: grabbed.bounds,
+                });
+                1
+            }
+            _ => 0,
+        }
+    }
+
+    /// Scale the sticker at `pos` around its center.
+    /// Returns the number of the actions added, which is 0 if it is merged into the last scroll.
+    pub fn scale_sticker(&mut self, pos: Point<Pixels>, factor: f32) -> usize {
+        const MERGE_INTERVAL: Duration = Duration::from_millis(500);
+
+        let Some(sticker) = self.sticker_at(pos) else {
+            return 0;
+        };
+
+        let longer_side = sticker.bounds.size.width.max(sticker.bounds.size.height);
+        let factor = factor.clamp(
+            CanvasSticker::MIN_SIZE / f32::from(longer_side),
+            CanvasSticker::MAX_SIZE * 4. / f32::from(longer_side),
+        );
+        let scaled = Bounds::centered_at(
+            sticker.bounds.center(),
+            sticker.bounds.size.map(|side| side * factor),
+        );
+
+        // The sticker being dragged is scaled as a part of the drag.
+        if let Some(grabbed) = &mut self.grabbed_sticker
+            && grabbed.id == sticker.id
+        {
+            grabbed.bounds = scaled;
+            return 0;
+        }
+
+        let merged = self
+            .last_sticker_scroll
+            .is_some_and(|scrolled_at| scrolled_at.elapsed() < MERGE_INTERVAL);
+        self.last_sticker_scroll = Some(Instant::now());
+
+        if merged
+            && let Some(CanvasAction::MoveSticker { id, bounds }) = self.stack.back_mut()
+            && *id == sticker.id
+        {
+            *bounds = scaled;
+            return 0;
+        }
+
+        self.painting = false;
+        self.push_action(CanvasAction::MoveSticker {
+            id: sticker.id,
+            bounds: scaled,
+        });
+
+        1
+    }
+
     fn push_action(&mut self, action: CanvasAction) {
         if self.stack.len() >= Self::MAX_STACK_SIZE {
             self.stack.pop_front();
         }
 
         self.stack.push_back(action);
+        self.undone.clear();
+    }
+
+    /// Get the labels of the last `count` actions in the order they were added.
+    pub fn last_action_labels(&self, count: usize) -> Vec<&'static str> {
+        let start_index = self.stack.len().saturating_sub(count);
+        self.stack
+            .range(start_index..)
+            .map(CanvasAction::label)
+            .collect()
     }
 
     pub fn is_painting(&self) -> bool {
         self.painting
     }
 
-    pub fn flush(&mut self) {
+    /// Finish the stroke. If `recognize_shapes` is true and the stroke roughly forms a shape,
+    /// the clean shape is added as another action, so undoing it brings the freehand stroke back.
+    /// Returns the number of the actions added by the stroke.
+    pub fn flush(&mut self, recognize_shapes: bool) -> usize {
         self.painting = false;
+
+        let shape = match self.stack.back() {
+            Some(CanvasAction::DrawLine(path)) if recognize_shapes => path.recognize(),
+            _ => None,
+        };
+
+        match shape {
+            Some(shape) => {
+                self.push_action(CanvasAction::Recognize(shape));
+                2
+            }
+            None => 1,
+        }
     }
 
     pub fn undo(&mut self) {
         self.painting = false;
-        self.stack.pop_back();
+        self.grabbed_sticker = None;
+
+        if let Some(action) = self.stack.pop_back() {
+            self.undone.push(action);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        self.painting = false;
+        self.grabbed_sticker = None;
+
+        if let Some(action) = self.undone.pop() {
+            self.stack.push_back(action);
+        }
     }
 
     pub fn clear(&mut self) {
@@ -287,11 +1326,169 @@ impl Canvas {
         self.push_action(CanvasAction::Clear);
     }
 
+    /// Rescale all strokes proportionally.
+    /// This is used when the logical size of the display is changed.
+    pub fn rescale(&mut self, scale_x: f32, scale_y: f32) {
+        for action in self.stack.iter_mut() {
+            match action {
+                CanvasAction::DrawLine(path) | CanvasAction::Recognize(path) => {
+                    path.rescale(scale_x, scale_y)
+                }
+                CanvasAction::Erase(eraser) => eraser.rescale(scale_x, scale_y),
+                CanvasAction::Stamp(stamp) => stamp.rescale(scale_x, scale_y),
+                CanvasAction::Sticker(CanvasSticker { bounds, .. })
+                | CanvasAction::MoveSticker { bounds, .. } => {
+                    CanvasSticker::rescale(bounds, scale_x, scale_y)
+                }
+                CanvasAction::Clear => {}
+            }
+        }
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
     pub fn set_highlight(&mut self, pos: Point<Pixels>) {
         self.highlight_pos = Some(pos);
     }
 
+    /// Get the position of the cursor which the tool preview is painted at.
+    pub fn highlight_pos(&self) -> Option<Point<Pixels>> {
+        self.highlight_pos
+    }
+
+    /// Get the position of the cursor, which is forgotten until the cursor moves again.
+    pub fn take_highlight(&mut self) -> Option<Point<Pixels>> {
+        self.highlight_pos.take()
+    }
+
     pub fn clear_highlight(&mut self) {
         self.highlight_pos = None;
     }
+
+    pub fn add_ripple(&mut self, pos: Point<Pixels>, color: Hsla) {
+        self.ripples.push(Ripple {
+            pos,
+            color,
+            started_at: Instant::now(),
+        });
+    }
+
+    /// Whether the canvas should be repainted for the animation.
+    pub fn is_animating(&self) -> bool {
+        !self.ripples.is_empty() || self.lens.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: Point<Pixels>, b: (f32, f32)) {
+        assert!(
+            (f32::from(a.x) - b.0).abs() < 0.01 && (f32::from(a.y) - b.1).abs() < 0.01,
+            "{a:?} is not near {b:?}"
+        );
+    }
+
+    /// Get the numbers of the numbered stamps on the canvas.
+    fn stamp_numbers(canvas: &Canvas) -> Vec<usize> {
+        canvas
+            .stack
+            .iter()
+            .filter_map(|action| match action {
+                CanvasAction::Stamp(stamp) if stamp.kind == StampKind::Number => Some(stamp.number),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn number_stamps_across_undo_and_clear() {
+        let mut canvas = Canvas::new();
+        let stamp = |canvas: &mut Canvas, kind| {
+            canvas.put_stamp(kind, point(px(10.), px(10.)), gpui::red());
+        };
+
+        stamp(&mut canvas, StampKind::Number);
+        // Other stamps do not count.
+        stamp(&mut canvas, StampKind::Check);
+        stamp(&mut canvas, StampKind::Number);
+        assert_eq!(stamp_numbers(&canvas), [1, 2]);
+
+        // Undoing the stamp gives its number to the next one.
+        canvas.undo();
+        stamp(&mut canvas, StampKind::Number);
+        assert_eq!(stamp_numbers(&canvas), [1, 2]);
+
+        // Clearing starts again from 1, and undoing the clear continues the numbers.
+        canvas.clear();
+        stamp(&mut canvas, StampKind::Number);
+        assert_eq!(stamp_numbers(&canvas), [1, 2, 1]);
+
+        canvas.undo();
+        canvas.undo();
+        stamp(&mut canvas, StampKind::Number);
+        assert_eq!(stamp_numbers(&canvas), [1, 2, 3]);
+    }
+
+    #[test]
+    fn move_stickers_on_release() {
+        let mut canvas = Canvas::new();
+        let image = Arc::new(RenderImage::new(vec![image::Frame::new(
+            image::RgbaImage::new(40, 20),
+        )]));
+        let source = StickerSource::Bundled("stickers/star.svg".into());
+        canvas.put_sticker(source, image, point(px(100.), px(100.)));
+
+        // A click without moving the sticker is not an action.
+        assert!(canvas.grab_sticker(point(px(100.), px(100.))));
+        assert_eq!(canvas.release_sticker(), 0);
+        assert_eq!(canvas.last_action_labels(10), ["Sticker"]);
+
+        // The sticker follows the cursor, and the move is added when it is released.
+        assert!(canvas.grab_sticker(point(px(100.), px(100.))));
+        canvas.drag_sticker(point(px(130.), px(110.)));
+        assert_eq!(canvas.last_action_labels(10), ["Sticker"]);
+        assert_near(canvas.visible_stickers()[0].bounds.center(), (130., 110.));
+
+        assert_eq!(canvas.release_sticker(), 1);
+        assert_eq!(canvas.last_action_labels(10), ["Sticker", "Move sticker"]);
+
+        canvas.undo();
+        assert_near(canvas.visible_stickers()[0].bounds.center(), (100., 100.));
+    }
+
+    #[test]
+    fn snap_line_to_15_degrees() {
+        let start = point(px(10.), px(10.));
+
+        // Nearly horizontal and vertical lines become straight.
+        assert_near(snap_line(start, point(px(110.), px(13.))), (110.04, 10.));
+        assert_near(snap_line(start, point(px(8.), px(-90.))), (10., -90.02));
+
+        // The length is kept while the angle is rounded to 45 degrees.
+        let end = snap_line(start, point(px(110.), px(105.)));
+        let length = 100f32.hypot(95.) / 2f32.sqrt();
+        assert_near(end, (10. + length, 10. + length));
+    }
+
+    #[test]
+    fn constrain_aspect_to_square() {
+        let start = point(px(100.), px(100.));
+        let end = point(px(160.), px(80.));
+
+        assert_near(constrain_aspect(start, end, false), (160., 80.));
+        // The longer side is used in the direction of the drag.
+        assert_near(constrain_aspect(start, end, true), (160., 40.));
+        assert_near(
+            constrain_aspect(start, point(px(90.), px(130.)), true),
+            (70., 130.),
+        );
+    }
 }
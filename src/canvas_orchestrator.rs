@@ -1,7 +1,10 @@
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
 
 use display_config::DisplayId;
-use gpui::{App, AppContext, Context, Entity, Global};
+use gpui::{App, AppContext, Context, Entity, EventEmitter, Global};
 
 use crate::canvas::Canvas;
 
@@ -10,10 +13,49 @@ pub enum ActionScope {
     All,
 }
 
+/// The action in the history, which can be undone and redone.
+pub struct HistoryEntry {
+    pub scope: ActionScope,
+    /// What the action did, e.g. `Pen stroke`.
+    pub label: &'static str,
+    pub added_at: Instant,
+}
+
+impl HistoryEntry {
+    fn new(scope: ActionScope, label: &'static str) -> Self {
+        Self {
+            scope,
+            label,
+            added_at: Instant::now(),
+        }
+    }
+}
+
+/// What has changed in `CanvasOrchestrator`.
+///
+/// The orchestrator is updated on every cursor move, so the views which show only a part of it
+/// subscribe to these events instead of observing the whole global.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrchestratorEvent {
+    /// An action is added, undone or redone.
+    HistoryChanged,
+    AnnotationsHiddenChanged,
+}
+
+/// Emits `OrchestratorEvent`, see `CanvasOrchestrator::events`.
+pub struct OrchestratorEvents;
+
+impl EventEmitter<OrchestratorEvent> for OrchestratorEvents {}
+
 pub struct CanvasOrchestrator {
     canvases: HashMap<DisplayId, Entity<Canvas>>,
-    action_history: VecDeque<ActionScope>,
+    events: Entity<OrchestratorEvents>,
+    action_history: VecDeque<HistoryEntry>,
+    /// The undone actions which can be redone until a new action is added.
+    undone_history: Vec<HistoryEntry>,
     cursor_display_pos: Option<DisplayId>,
+    /// Whether the annotations on all canvases are hidden without being cleared.
+    annotations_hidden: bool,
 }
 
 impl Global for CanvasOrchestrator {}
@@ -24,40 +66,89 @@ impl CanvasOrchestrator {
     pub fn register_global(cx: &mut App) {
         let orchestrator = Self {
             canvases: HashMap::new(),
+            events: cx.new(|_| OrchestratorEvents),
             action_history: VecDeque::new(),
+            undone_history: Vec::new(),
             cursor_display_pos: None,
+            annotations_hidden: false,
         };
         cx.set_global(orchestrator);
     }
 
     pub fn add_canvas(&mut self, cx: &mut App, display_id: DisplayId) {
-        self.canvases.insert(display_id, cx.new(|_| Canvas::new()));
+        let hidden = self.annotations_hidden;
+
+        self.canvases.insert(
+            display_id,
+            cx.new(|_| {
+                let mut canvas = Canvas::new();
+                canvas.set_hidden(hidden);
+                canvas
+            }),
+        );
     }
 
     pub fn remove_canvas(&mut self, display_id: &DisplayId) {
         self.canvases.remove(display_id);
     }
 
+    /// Get the entity to subscribe to the changes, see `OrchestratorEvent`.
+    pub fn events(&self) -> &Entity<OrchestratorEvents> {
+        &self.events
+    }
+
+    fn emit(&self, cx: &mut App, event: OrchestratorEvent) {
+        self.events.update(cx, |_, cx| cx.emit(event));
+    }
+
     pub fn undo(&mut self, cx: &mut App) {
-        if let Some(scope) = self.action_history.pop_back() {
-            match scope {
-                ActionScope::Display(display_id) => {
-                    if let Some(canvas) = self.canvases.get(&display_id) {
-                        canvas.update(cx, |canvas, cx| {
-                            canvas.undo();
-                            cx.notify();
-                        });
-                    }
-                }
-                ActionScope::All => {
-                    for canvas in self.canvases.values() {
-                        canvas.update(cx, |canvas, cx| {
-                            canvas.undo();
-                            cx.notify();
-                        });
-                    }
-                }
-            };
+        if let Some(entry) = self.action_history.pop_back() {
+            self.update_scope(cx, &entry.scope, Canvas::undo);
+            self.undone_history.push(entry);
+            self.emit(cx, OrchestratorEvent::HistoryChanged);
+        }
+    }
+
+    pub fn redo(&mut self, cx: &mut App) {
+        if let Some(entry) = self.undone_history.pop() {
+            self.update_scope(cx, &entry.scope, Canvas::redo);
+            self.action_history.push_back(entry);
+            self.emit(cx, OrchestratorEvent::HistoryChanged);
+        }
+    }
+
+    /// Get the actions done, from the oldest.
+    pub fn history(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.action_history.iter()
+    }
+
+    /// Get the undone actions in the order they are redone.
+    pub fn undone_history(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.undone_history.iter().rev()
+    }
+
+    /// Undo or redo the actions until `count` actions of the history are done.
+    pub fn jump_to(&mut self, cx: &mut App, count: usize) {
+        while self.action_history.len() > count {
+            self.undo(cx);
+        }
+
+        while self.action_history.len() < count && !self.undone_history.is_empty() {
+            self.redo(cx);
+        }
+    }
+
+    fn update_scope(&self, cx: &mut App, scope: &ActionScope, f: impl Fn(&mut Canvas)) {
+        let canvases: Vec<_> = match scope {
+            ActionScope::Display(display_id) => self.canvases.get(display_id).into_iter().collect(),
+            ActionScope::All => self.canvases.values().collect(),
+        };
+
+        for canvas in canvases {
+            canvas.update(cx, |canvas, cx| {
+                f(canvas);
+                cx.notify();
+            });
         }
     }
 
@@ -69,30 +160,37 @@ impl CanvasOrchestrator {
             });
         }
 
-        self.push_history(ActionScope::All);
+        self.push_history(cx, HistoryEntry::new(ActionScope::All, "Clear all"));
     }
 
+    /// Update the canvas with `f`, which returns the number of the actions added to the canvas.
     pub fn action_canvas(
         &mut self,
         cx: &mut App,
         display_id: DisplayId,
-        f: impl FnOnce(&mut Canvas, &mut Context<'_, Canvas>) -> bool,
+        f: impl FnOnce(&mut Canvas, &mut Context<'_, Canvas>) -> usize,
     ) {
         if let Some(canvas) = self.canvases.get_mut(&display_id) {
-            let action = canvas.update(cx, f);
+            let actions = canvas.update(cx, f);
+            let labels = canvas.read(cx).last_action_labels(actions);
 
-            if action {
-                self.push_history(ActionScope::Display(display_id));
+            for label in labels {
+                self.push_history(
+                    cx,
+                    HistoryEntry::new(ActionScope::Display(display_id.clone()), label),
+                );
             }
         }
     }
 
-    fn push_history(&mut self, scope: ActionScope) {
+    fn push_history(&mut self, cx: &mut App, entry: HistoryEntry) {
         if self.action_history.len() >= Self::MAX_ACTION_HISTORY {
             self.action_history.pop_front();
         }
 
-        self.action_history.push_back(scope);
+        self.action_history.push_back(entry);
+        self.undone_history.clear();
+        self.emit(cx, OrchestratorEvent::HistoryChanged);
     }
 
     pub fn update_canvas(
@@ -106,6 +204,39 @@ impl CanvasOrchestrator {
         }
     }
 
+    pub fn are_annotations_hidden(&self) -> bool {
+        self.annotations_hidden
+    }
+
+    /// Hide or show the annotations on all canvases. Hiding them does not clear them.
+    pub fn set_annotations_hidden(&mut self, cx: &mut App, hidden: bool) {
+        self.annotations_hidden = hidden;
+        self.emit(cx, OrchestratorEvent::AnnotationsHiddenChanged);
+
+        for canvas in self.canvases.values() {
+            canvas.update(cx, |canvas, cx| {
+                canvas.set_hidden(hidden);
+                cx.notify();
+            });
+        }
+    }
+
+    pub fn toggle_annotations_hidden(&mut self, cx: &mut App) {
+        self.set_annotations_hidden(cx, !self.annotations_hidden);
+    }
+
+    /// Get the display which the cursor was on last.
+    pub fn cursor_display(&self) -> Option<&DisplayId> {
+        self.cursor_display_pos.as_ref()
+    }
+
+    /// Repaint all canvases, e.g. when the way to paint them is changed.
+    pub fn notify_canvases(&self, cx: &mut App) {
+        for canvas in self.canvases.values() {
+            canvas.update(cx, |_, cx| cx.notify());
+        }
+    }
+
     pub fn notify_old_working_canvas(&mut self, cx: &mut App, new_display_id: Option<&DisplayId>) {
         if let Some(new_display_id) = new_display_id {
             // When moving from one canvas window to another,
@@ -0,0 +1,415 @@
+use gpui::{
+    AnyElement, App, IntoElement, Modifiers, MouseButton, Pixels, Point, ReadGlobal, Window, px,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::{self, Canvas, DrawMode, StrokePoint, ToolState},
+    pen::PenInput,
+    settings::{Settings, SpotlightShape},
+    sticker::StickerSource,
+    ui_tool_options::{self, OPTION_ROW_HEIGHT},
+};
+
+/// Declare the tools in the order of the toolbar buttons.
+///
+/// Each entry names the variant of `Tool`, which is also the name in the settings file,
+/// and the type implementing `CanvasTool`. A new tool is added by listing it here only.
+macro_rules! register_tools {
+    ($($id:ident => $tool:ident),* $(,)?) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        pub enum Tool {
+            $($id),*
+        }
+
+        impl Tool {
+            /// All tools in the order of the toolbar buttons.
+            pub const ALL: &[Tool] = &[$(Self::$id),*];
+
+            pub fn canvas_tool(self) -> &'static dyn CanvasTool {
+                match self {
+                    $(Self::$id => &$tool),*
+                }
+            }
+        }
+    };
+}
+
+register_tools! {
+    Cursor => CursorTool,
+    Pen => PenTool,
+    Eraser => EraserTool,
+    Highlight => HighlightTool,
+    Spotlight => SpotlightTool,
+    Magnifier => MagnifierTool,
+    Stamp => StampTool,
+    Sticker => StickerTool,
+}
+
+impl Tool {
+    /// Whether the canvas windows receive the cursor events with this tool.
+    pub fn is_canvas_related(self) -> bool {
+        self.canvas_tool().captures_cursor()
+    }
+}
+
+/// The press, the drag or the release of a mouse button on the canvas.
+pub struct PointerEvent {
+    pub position: Point<Pixels>,
+    pub button: MouseButton,
+    pub modifiers: Modifiers,
+}
+
+/// The scroll of the mouse wheel on the canvas.
+pub struct ScrollEvent {
+    pub position: Point<Pixels>,
+    /// The scrolled distance in pixels, positive when it is scrolled up.
+    pub amount: f32,
+    pub modifiers: Modifiers,
+}
+
+/// What a tool does on the canvas.
+///
+/// The handlers returning `usize` return the number of the actions added to the canvas,
+/// so that they can be undone.
+pub trait CanvasTool: Sync {
+    /// The name shown in the menus.
+    fn label(&self) -> &'static str;
+
+    /// The path of the icon on the toolbar button.
+    fn icon(&self) -> &'static str;
+
+    /// Whether the canvas windows receive the cursor events with this tool.
+    /// Otherwise the clicks go through to the applications under the canvas.
+    fn captures_cursor(&self) -> bool {
+        false
+    }
+
+    /// Whether the wheel turned with Alt is passed to `on_scroll` while the clicks go through
+    /// the canvas, see `platform_impl::listen_wheel`.
+    fn takes_wheel(&self) -> bool {
+        false
+    }
+
+    /// Whether the canvas is repainted as the cursor moves, so that the preview follows it.
+    fn follows_cursor(&self) -> bool {
+        false
+    }
+
+    /// Whether the eraser end of the stylus switches to this tool, see `PenInput`.
+    fn is_stylus_eraser(&self) -> bool {
+        false
+    }
+
+    /// The height of the options shown below the tools while this tool is chosen.
+    fn options_height(&self) -> Pixels {
+        px(0.)
+    }
+
+    /// Render the options shown below the tools while this tool is chosen, e.g. the stickers.
+    /// It is as tall as `options_height`.
+    fn render_options(&self, _cx: &App) -> Option<AnyElement> {
+        None
+    }
+
+    /// Paint the preview of the tool over the annotations, e.g. the highlight at the cursor.
+    /// The position of the cursor is kept by the canvas, see `Canvas::highlight_pos`.
+    fn paint_preview(&self, _canvas: &mut Canvas, _window: &mut Window, _settings: &Settings) {}
+
+    /// Called when the left button is pressed.
+    fn on_pointer_down(&self, _canvas: &mut Canvas, _cx: &mut App, _event: &PointerEvent) -> usize {
+        0
+    }
+
+    /// Called when the cursor is moved with the left or the right button pressed.
+    /// If it returns false, the stroke being painted is finished as when the button is released.
+    fn on_pointer_drag(&self, _canvas: &mut Canvas, _cx: &mut App, _event: &PointerEvent) -> bool {
+        false
+    }
+
+    /// Called when the left button is released. Returns the number of the actions added.
+    fn on_pointer_up(&self, _canvas: &mut Canvas, _cx: &mut App) -> usize {
+        0
+    }
+
+    fn on_scroll(&self, _canvas: &mut Canvas, _cx: &mut App, _event: &ScrollEvent) -> usize {
+        0
+    }
+}
+
+struct CursorTool;
+
+impl CanvasTool for CursorTool {
+    fn label(&self) -> &'static str {
+        "Cursor"
+    }
+
+    fn icon(&self) -> &'static str {
+        "icons/mouse-pointer-2.svg"
+    }
+}
+
+struct PenTool;
+
+impl CanvasTool for PenTool {
+    fn label(&self) -> &'static str {
+        "Pen"
+    }
+
+    fn icon(&self) -> &'static str {
+        "icons/pencil.svg"
+    }
+
+    fn captures_cursor(&self) -> bool {
+        true
+    }
+
+    fn on_pointer_drag(&self, canvas: &mut Canvas, cx: &mut App, event: &PointerEvent) -> bool {
+        let point = StrokePoint::new(event.position, PenInput::global(cx).current());
+        canvas.draw(cx, point, DrawMode::new(event.button, &event.modifiers));
+
+        true
+    }
+}
+
+struct EraserTool;
+
+impl CanvasTool for EraserTool {
+    fn label(&self) -> &'static str {
+        "Eraser"
+    }
+
+    fn icon(&self) -> &'static str {
+        "icons/eraser.svg"
+    }
+
+    fn captures_cursor(&self) -> bool {
+        true
+    }
+
+    fn is_stylus_eraser(&self) -> bool {
+        true
+    }
+
+    fn on_pointer_drag(&self, canvas: &mut Canvas, cx: &mut App, event: &PointerEvent) -> bool {
+        let point = StrokePoint::new(event.position, PenInput::global(cx).current());
+        canvas.draw(cx, point, DrawMode::Erase);
+
+        true
+    }
+}
+
+struct HighlightTool;
+
+impl CanvasTool for HighlightTool {
+    fn label(&self) -> &'static str {
+        "Highlight"
+    }
+
+    fn icon(&self) -> &'static str {
+        "icons/circle.svg"
+    }
+
+    fn follows_cursor(&self) -> bool {
+        true
+    }
+
+    fn paint_preview(&self, canvas: &mut Canvas, window: &mut Window, settings: &Settings) {
+        if let Some(pos) = canvas.take_highlight() {
+            canvas::paint_highlight(window, pos, &settings.highlight);
+        }
+    }
+}
+
+struct SpotlightTool;
+
+impl CanvasTool for SpotlightTool {
+    fn label(&self) -> &'static str {
+        "Spotlight"
+    }
+
+    fn icon(&self) -> &'static str {
+        "icons/flashlight.svg"
+    }
+
+    // The spotlight is adjusted with the mouse wheel while the clicks go through it.
+    fn takes_wheel(&self) -> bool {
+        true
+    }
+
+    fn follows_cursor(&self) -> bool {
+        true
+    }
+
+    /// Without the cursor on this canvas, the whole canvas is dimmed.
+    fn paint_preview(&self, canvas: &mut Canvas, window: &mut Window, settings: &Settings) {
+        canvas::paint_spotlight(window, canvas.take_highlight(), &settings.spotlight);
+    }
+
+    /// The wheel changes the radius, with shift it changes the opacity
+    /// and with the platform modifier it switches the shape.
+    /// The settings file is written once the wheel stops, see `Settings::update`.
+    fn on_scroll(&self, canvas: &mut Canvas, cx: &mut App, event: &ScrollEvent) -> usize {
+        Settings::update(cx, |settings| {
+            let spotlight = &mut settings.spotlight;
+
+            if event.modifiers.secondary() {
+                spotlight.shape = if event.amount > 0. {
+                    SpotlightShape::Circle
+                } else {
+                    SpotlightShape::Rectangle
+                };
+            } else if event.modifiers.shift {
+                spotlight.set_opacity(spotlight.opacity + event.amount / 400.);
+            } else {
+                spotlight.set_radius(spotlight.radius + event.amount / 2.);
+            }
+        });
+
+        canvas.set_highlight(event.position);
+        0
+    }
+}
+
+struct MagnifierTool;
+
+impl CanvasTool for MagnifierTool {
+    fn label(&self) -> &'static str {
+        "Magnifier"
+    }
+
+    fn icon(&self) -> &'static str {
+        "icons/zoom-in.svg"
+    }
+
+    // The magnifier is adjusted with the mouse wheel while the clicks go through it.
+    fn takes_wheel(&self) -> bool {
+        true
+    }
+
+    fn follows_cursor(&self) -> bool {
+        true
+    }
+
+    /// The magnifier keeps showing the screen under the cursor until it leaves.
+    fn paint_preview(&self, canvas: &mut Canvas, window: &mut Window, settings: &Settings) {
+        if let Some(pos) = canvas.highlight_pos() {
+            canvas.paint_lens(window, pos, &settings.magnifier);
+        }
+    }
+
+    /// The wheel changes the zoom factor and with shift it changes the radius.
+    /// The settings file is written once the wheel stops, see `Settings::update`.
+    fn on_scroll(&self, canvas: &mut Canvas, cx: &mut App, event: &ScrollEvent) -> usize {
+        Settings::update(cx, |settings| {
+            let magnifier = &mut settings.magnifier;
+
+            if event.modifiers.shift {
+                magnifier.set_radius(magnifier.radius + event.amount / 2.);
+            } else {
+                magnifier.set_zoom(magnifier.zoom + event.amount / 80.);
+            }
+        });
+
+        canvas.set_highlight(event.position);
+        0
+    }
+}
+
+struct StampTool;
+
+impl CanvasTool for StampTool {
+    fn label(&self) -> &'static str {
+        "Stamp"
+    }
+
+    fn icon(&self) -> &'static str {
+        "icons/stamp.svg"
+    }
+
+    fn captures_cursor(&self) -> bool {
+        true
+    }
+
+    fn options_height(&self) -> Pixels {
+        px(OPTION_ROW_HEIGHT)
+    }
+
+    /// The kinds of the stamps.
+    fn render_options(&self, cx: &App) -> Option<AnyElement> {
+        Some(ui_tool_options::render_stamp_select(cx).into_any_element())
+    }
+
+    fn on_pointer_down(&self, canvas: &mut Canvas, cx: &mut App, event: &PointerEvent) -> usize {
+        canvas.stamp(cx, event.position);
+        1
+    }
+}
+
+struct StickerTool;
+
+impl CanvasTool for StickerTool {
+    fn label(&self) -> &'static str {
+        "Sticker"
+    }
+
+    fn icon(&self) -> &'static str {
+        "icons/sticker.svg"
+    }
+
+    fn captures_cursor(&self) -> bool {
+        true
+    }
+
+    fn options_height(&self) -> Pixels {
+        px(OPTION_ROW_HEIGHT)
+    }
+
+    /// The stickers to put, which are listed again to pick up the ones added by the user.
+    fn render_options(&self, cx: &App) -> Option<AnyElement> {
+        let stickers = StickerSource::list();
+        Some(ui_tool_options::render_sticker_select(cx, &stickers).into_any_element())
+    }
+
+    /// Start dragging the sticker under the cursor, or put the selected sticker if there is none.
+    fn on_pointer_down(&self, canvas: &mut Canvas, cx: &mut App, event: &PointerEvent) -> usize {
+        // The move is added when the sticker is released, see `on_pointer_up`.
+        if canvas.grab_sticker(event.position) {
+            return 0;
+        }
+
+        let Some(source) = ToolState::global(cx).sticker.clone() else {
+            return 0;
+        };
+
+        match source.load(cx) {
+            Ok(image) => {
+                canvas.put_sticker(source, image, event.position);
+                1
+            }
+            Err(error) => {
+                eprintln!("Failed to load the sticker: {error:#}");
+                0
+            }
+        }
+    }
+
+    fn on_pointer_drag(&self, canvas: &mut Canvas, _cx: &mut App, event: &PointerEvent) -> bool {
+        if event.button != MouseButton::Left {
+            return false;
+        }
+
+        canvas.drag_sticker(event.position);
+        true
+    }
+
+    fn on_pointer_up(&self, canvas: &mut Canvas, _cx: &mut App) -> usize {
+        canvas.release_sticker()
+    }
+
+    /// The wheel scales the sticker under the cursor.
+    fn on_scroll(&self, canvas: &mut Canvas, _cx: &mut App, event: &ScrollEvent) -> usize {
+        canvas.scale_sticker(event.position, 1. + event.amount / 200.)
+    }
+}
@@ -1,28 +1,107 @@
 use display_config::{Display, DisplayId};
-use gpui::{AnyWindowHandle, App, Entity, ReadGlobal};
+use gpui::{
+    AnyWindowHandle, App, AppContext, Bounds, Entity, Modifiers, Pixels, Point, ReadGlobal,
+    UpdateGlobal,
+};
 
 use crate::{
     canvas::ToolState,
-    platform_impl::WindowExt,
-    ui_canvas::CanvasView,
-    utils::{self, dpi_size_to_gpui},
+    canvas_orchestrator::CanvasOrchestrator,
+    canvas_tool::ScrollEvent,
+    platform_impl::{
+        ClickButton, MouseClick, TargetWindow, TargetWindowEvent, Tracker, WheelEvent, WindowExt,
+    },
+    settings::Settings,
+    ui_canvas::{CanvasView, WHEEL_LINE_HEIGHT},
+    utils,
 };
 
+/// Create the options of a transparent window which covers the given area of the desktop.
+pub fn overlay_window_options(
+    origin: dpi::LogicalPosition<i32>,
+    size: dpi::LogicalSize<u32>,
+) -> gpui::WindowOptions {
+    let bounds = gpui::Bounds::new(
+        utils::dpi_pos_to_gpui(origin),
+        utils::dpi_size_to_gpui(size),
+    );
+    let window_bounds = Some(gpui::WindowBounds::Windowed(bounds));
+
+    gpui::WindowOptions {
+        titlebar: None,
+        kind: gpui::WindowKind::PopUp,
+        app_id: Some(crate::APP_IDENTIFIER.to_owned()),
+        window_background: gpui::WindowBackgroundAppearance::Transparent,
+        window_bounds,
+        focus: false,
+        ..Default::default()
+    }
+}
+
+/// Move and resize the window to the given area of the desktop.
+pub fn set_window_rect(
+    window: &mut gpui::Window,
+    origin: dpi::LogicalPosition<i32>,
+    size: dpi::LogicalSize<u32>,
+) {
+    #[cfg(target_os = "windows")]
+    {
+        // NOTE: `window_bounds` is not working on Windows so we move the window manually.
+
+        use crate::platform_impl::windows::WindowsWindowExt;
+
+        window.set_window_rect(origin.x, origin.y, size.width as _, size.height as _);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        window.set_position(origin);
+        window.resize(utils::dpi_size_to_gpui(size));
+    }
+}
+
+/// The application window which the canvas follows.
+pub struct PinnedWindow {
+    id: u64,
+    scale_factor: f64,
+    rect: Option<(dpi::LogicalPosition<i32>, dpi::LogicalSize<u32>)>,
+    minimized: bool,
+    _tracker: Tracker,
+}
+
+/// What decides the area which the canvas window covers.
+pub enum CanvasSource {
+    /// The canvas covers the whole display.
+    Display,
+    /// The canvas covers the region of the display, relative to the display origin.
+    Region(Bounds<Pixels>),
+    /// The canvas follows the application window.
+    Window(PinnedWindow),
+}
+
 pub struct CanvasWindow {
-    _display_id: DisplayId,
+    display_id: DisplayId,
+    origin: dpi::LogicalPosition<i32>,
+    size: dpi::LogicalSize<u32>,
+    source: CanvasSource,
     window_handle: AnyWindowHandle,
-    _view: Entity<CanvasView>,
+    view: Entity<CanvasView>,
 }
 
 impl CanvasWindow {
     pub fn new(cx: &mut App, display: Display) -> Self {
         let display_id = display.id.clone();
+        let origin = display.origin;
+        let size = display.size;
         let (window_handle, view) = Self::setup_canvas_window(cx, display);
 
         Self {
-            _display_id: display_id,
+            display_id,
+            origin,
+            size,
+            source: CanvasSource::Display,
             window_handle,
-            _view: view,
+            view,
         }
     }
 
@@ -30,21 +109,7 @@ impl CanvasWindow {
         cx: &mut App,
         display: Display,
     ) -> (AnyWindowHandle, Entity<CanvasView>) {
-        let bounds = gpui::Bounds::new(
-            utils::dpi_pos_to_gpui(display.origin),
-            utils::dpi_size_to_gpui(display.size),
-        );
-        let window_bounds = Some(gpui::WindowBounds::Windowed(bounds));
-
-        let window_options = gpui::WindowOptions {
-            titlebar: None,
-            kind: gpui::WindowKind::PopUp,
-            app_id: Some(crate::APP_IDENTIFIER.to_owned()),
-            window_background: gpui::WindowBackgroundAppearance::Transparent,
-            window_bounds,
-            focus: false,
-            ..Default::default()
-        };
+        let window_options = overlay_window_options(display.origin, display.size);
 
         let mut created_view = std::cell::OnceCell::new();
         let handle = *cx
@@ -56,15 +121,7 @@ impl CanvasWindow {
                 };
 
                 #[cfg(target_os = "windows")]
-                {
-                    // NOTE: `window_bounds` is not working on Windows so we move the window manually.
-
-                    use crate::platform_impl::windows::WindowsWindowExt;
-
-                    let origin = display.origin;
-                    let size = display.size;
-                    window.set_window_rect(origin.x, origin.y, size.width as _, size.height as _);
-                }
+                set_window_rect(window, display.origin, display.size);
 
                 let view = CanvasView::new(cx, window.window_handle(), display.id);
                 created_view.set(view.clone()).unwrap();
@@ -77,33 +134,98 @@ impl CanvasWindow {
     }
 
     #[cfg(target_os = "windows")]
-    pub fn on_mouse_move(&self, cx: &mut App, x: f32, y: f32) {
-        use gpui::{AppContext, UpdateGlobal, point, px};
+    pub fn on_mouse_move(&self, cx: &mut App, display: &Display, x: f32, y: f32) {
+        let position = dpi::PhysicalPosition::new(x, y).into();
+        let Some(mouse_pos) = self.position_in_window(display, position) else {
+            // If there are no mouse on this canvas window, do nothing.
+            return;
+        };
 
-        use crate::canvas_orchestrator::CanvasOrchestrator;
+        cx.update_entity(&self.view, |view, cx| {
+            CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+                view.on_mouse_move_whenever_window_inactive(cx, orchestrator, mouse_pos);
+            });
+        });
+    }
 
-        let (window_bounds, scale_factor) = cx
-            .update_window(self.window_handle, |_, window, _| {
-                (window.bounds(), window.scale_factor())
-            })
-            .unwrap();
+    /// Convert the position on the desktop to the one in this window on `display`,
+    /// if it is on this window.
+    ///
+    /// The physical position is converted with the scale factor of the display,
+    /// because the displays with different scale factors do not share a logical space.
+    fn position_in_window(
+        &self,
+        display: &Display,
+        position: dpi::Position,
+    ) -> Option<Point<Pixels>> {
+        let position = match position {
+            dpi::Position::Logical(position) => position,
+            dpi::Position::Physical(position) => {
+                let scale_factor = display.scale_factor;
+                let origin = display.origin.to_physical::<f64>(scale_factor);
+                let size = display.size.to_physical::<f64>(scale_factor);
 
-        let mut mouse_pos = point(px(x / scale_factor), px(y / scale_factor));
-        if !window_bounds.contains(&mouse_pos) {
-            // If there are no mouse on this canvas window, do nothing.
+                let x = position.x as f64 - origin.x;
+                let y = position.y as f64 - origin.y;
+                if !(0. ..size.width).contains(&x) || !(0. ..size.height).contains(&y) {
+                    return None;
+                }
+
+                dpi::LogicalPosition::new(
+                    display.origin.x as f64 + x / scale_factor,
+                    display.origin.y as f64 + y / scale_factor,
+                )
+            }
+        };
+
+        let (origin, size) = self.window_rect();
+        let x = position.x - origin.x as f64;
+        let y = position.y - origin.y as f64;
+
+        ((0. ..size.width as f64).contains(&x) && (0. ..size.height as f64).contains(&y))
+            .then(|| gpui::point(gpui::px(x as f32), gpui::px(y as f32)))
+    }
+
+    /// Show a ripple on the canvas if the click is on this window.
+    pub fn on_click(&self, cx: &mut App, display: &Display, click: MouseClick) {
+        let Some(pos) = self.position_in_window(display, click.position) else {
             return;
-        }
+        };
 
-        mouse_pos.x -= window_bounds.origin.x;
-        mouse_pos.y -= window_bounds.origin.y;
+        let ripple = &Settings::global(cx).ripple;
+        let color = match click.button {
+            ClickButton::Left => ripple.left_color,
+            ClickButton::Right => ripple.right_color,
+        };
 
-        cx.update_entity(&self._view, |view, cx| {
-            CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
-                view.on_mouse_move_whenever_window_inactive(cx, orchestrator, mouse_pos);
+        CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+            orchestrator.update_canvas(cx, &self.display_id, |canvas, cx| {
+                canvas.add_ripple(pos, color);
+                cx.notify();
             });
         });
     }
 
+    /// Let the tool handle the wheel if the cursor is on this window.
+    pub fn on_wheel(&self, cx: &mut App, display: &Display, wheel: WheelEvent) {
+        let Some(position) = self.position_in_window(display, wheel.position) else {
+            return;
+        };
+
+        let event = ScrollEvent {
+            position,
+            amount: wheel.notches * f32::from(WHEEL_LINE_HEIGHT),
+            modifiers: Modifiers {
+                alt: true,
+                shift: wheel.shift,
+                control: wheel.control,
+                ..Default::default()
+            },
+        };
+
+        cx.update_entity(&self.view, |view, cx| view.scroll(cx, event));
+    }
+
     pub fn set_ignore_cursor_events(&self, cx: &mut App, ignore: bool) {
         self.window_handle
             .update(cx, move |_, window, _| {
@@ -112,17 +234,173 @@ impl CanvasWindow {
             .unwrap();
     }
 
-    pub fn set_size(&self, cx: &mut App, size: dpi::LogicalSize<u32>) {
-        let size = dpi_size_to_gpui(size);
+    pub fn display_id(&self) -> &DisplayId {
+        &self.display_id
+    }
+
+    pub fn region(&self) -> Option<Bounds<Pixels>> {
+        match self.source {
+            CanvasSource::Region(region) => Some(region),
+            _ => None,
+        }
+    }
+
+    /// Get the ID of the application window which the canvas is pinned to.
+    pub fn pinned_window_id(&self) -> Option<u64> {
+        match &self.source {
+            CanvasSource::Window(pinned) => Some(pinned.id),
+            _ => None,
+        }
+    }
+
+    fn set_source(&mut self, cx: &mut App, source: CanvasSource) {
+        let was_minimized =
+            matches!(&self.source, CanvasSource::Window(pinned) if pinned.minimized);
+        let framed = !matches!(source, CanvasSource::Display);
+
+        self.source = source;
+        self.update_window_rect(cx);
+
+        if was_minimized {
+            self.set_hidden(cx, false);
+        }
+
+        cx.update_entity(&self.view, |view, cx| {
+            view.set_framed(framed);
+            cx.notify();
+        });
+    }
+
+    /// Limit the canvas to the region of the display, or cover the whole display with `None`.
+    pub fn set_region(&mut self, cx: &mut App, region: Option<Bounds<Pixels>>) {
+        let source = match region {
+            Some(region) => CanvasSource::Region(region),
+            None => CanvasSource::Display,
+        };
+
+        self.set_source(cx, source);
+    }
+
+    /// Make the canvas follow the application window.
+    /// The events of the window should be passed to [`Self::on_target_window_event`].
+    pub fn pin_to_window(
+        &mut self,
+        cx: &mut App,
+        target: &TargetWindow,
+        scale_factor: f64,
+        tracker: Tracker,
+    ) {
+        let pinned = PinnedWindow {
+            id: target.id,
+            scale_factor,
+            rect: Some((
+                target.origin.to_logical(scale_factor),
+                target.size.to_logical(scale_factor),
+            )),
+            minimized: false,
+            _tracker: tracker,
+        };
+
+        self.set_source(cx, CanvasSource::Window(pinned));
+    }
+
+    pub fn on_target_window_event(&mut self, cx: &mut App, id: u64, event: TargetWindowEvent) {
+        let CanvasSource::Window(pinned) = &mut self.source else {
+            return;
+        };
+
+        // The event may come from the window which the canvas was pinned to before.
+        if pinned.id != id {
+            return;
+        }
+
+        match event {
+            TargetWindowEvent::Moved { origin, size } => {
+                pinned.rect = Some((
+                    origin.to_logical(pinned.scale_factor),
+                    size.to_logical(pinned.scale_factor),
+                ));
+
+                self.update_window_rect(cx);
+            }
+            TargetWindowEvent::Minimized => {
+                pinned.minimized = true;
+                self.set_hidden(cx, true);
+            }
+            TargetWindowEvent::Restored => {
+                pinned.minimized = false;
+                self.set_hidden(cx, false);
+            }
+            TargetWindowEvent::Closed => self.set_source(cx, CanvasSource::Display),
+        }
+    }
+
+    /// Get the area of the desktop which the window should cover.
+    pub fn window_rect(&self) -> (dpi::LogicalPosition<i32>, dpi::LogicalSize<u32>) {
+        match &self.source {
+            CanvasSource::Display => (self.origin, self.size),
+            CanvasSource::Region(region) => {
+                let (origin, size) = utils::gpui_bounds_to_dpi(*region);
+                let origin =
+                    dpi::LogicalPosition::new(self.origin.x + origin.x, self.origin.y + origin.y);
+
+                (origin, size)
+            }
+            CanvasSource::Window(pinned) => pinned.rect.unwrap_or((self.origin, self.size)),
+        }
+    }
+
+    fn update_window_rect(&self, cx: &mut App) {
+        let (origin, size) = self.window_rect();
 
         self.window_handle
             .update(cx, move |_, window, _| {
-                window.resize(size);
-                // window.refresh();
+                set_window_rect(window, origin, size)
             })
             .unwrap();
     }
 
+    pub fn set_size(&mut self, cx: &mut App, size: dpi::LogicalSize<u32>) {
+        if self.size == size {
+            return;
+        }
+
+        let scale_x = size.width as f32 / self.size.width as f32;
+        let scale_y = size.height as f32 / self.size.height as f32;
+        self.size = size;
+
+        match &mut self.source {
+            CanvasSource::Display => {}
+            CanvasSource::Region(region) => {
+                region.origin.x *= scale_x;
+                region.origin.y *= scale_y;
+                region.size.width *= scale_x;
+                region.size.height *= scale_y;
+            }
+            // The canvas pinned to a window does not depend on the display size.
+            CanvasSource::Window(_) => return,
+        }
+
+        self.update_window_rect(cx);
+
+        // Keep the strokes at the same place relative to the display.
+        CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+            orchestrator.update_canvas(cx, &self.display_id, |canvas, cx| {
+                canvas.rescale(scale_x, scale_y);
+                cx.notify();
+            });
+        });
+    }
+
+    pub fn set_origin(&mut self, cx: &mut App, origin: dpi::LogicalPosition<i32>) {
+        if self.origin == origin {
+            return;
+        }
+
+        self.origin = origin;
+        self.update_window_rect(cx);
+    }
+
     pub fn set_hidden(&self, cx: &mut App, hidden: bool) {
         self.window_handle
             .update(cx, move |_, window, _| window.set_hidden(hidden))
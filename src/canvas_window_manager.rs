@@ -1,12 +1,25 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use display_config::{DisplayId, DisplayObserver, Event as DisplayEvent, get_displays};
-use gpui::{App, AsyncApp, Global};
+use display_config::{Display, DisplayId, DisplayObserver, Event as DisplayEvent, get_displays};
+use gpui::{App, AsyncApp, Bounds, Global, Pixels, ReadGlobal, UpdateGlobal};
 
-use crate::canvas_window::CanvasWindow;
+use crate::{
+    canvas::ToolState,
+    canvas_tool::Tool,
+    canvas_window::CanvasWindow,
+    platform_impl::{self, MouseClick, TargetWindow, TargetWindowEvent, Tracker, WheelEvent},
+    region_window::RegionWindow,
+    settings::Settings,
+};
 
 pub struct CanvasWindowManager {
+    displays: HashMap<DisplayId, Display>,
     windows: HashMap<DisplayId, CanvasWindow>,
+    region_windows: Vec<RegionWindow>,
+    /// It is running while the click ripples are enabled.
+    click_listener: Option<Tracker>,
+    /// It is running while the tool takes the wheel, see `CanvasTool::takes_wheel`.
+    wheel_listener: Option<Tracker>,
     _display_observer: DisplayObserver,
 }
 
@@ -21,10 +34,22 @@ impl CanvasWindowManager {
             tx.send_blocking(event).unwrap();
         });
 
-        let manager = Self {
-            windows: Self::setup_canvas_windows(cx),
+        let displays: HashMap<_, _> = get_displays()
+            .unwrap()
+            .into_iter()
+            .map(|display| (display.id.clone(), display))
+            .collect();
+
+        let mut manager = Self {
+            windows: Self::setup_canvas_windows(cx, &displays),
+            displays,
+            region_windows: Vec::new(),
+            click_listener: None,
+            wheel_listener: None,
             _display_observer: display_observer,
         };
+        manager.update_click_listener(cx);
+        manager.update_wheel_listener(cx, ToolState::global(cx).tool());
         cx.set_global(manager);
 
         cx.spawn(async move |cx| Self::listener(cx, rx).await)
@@ -54,23 +79,28 @@ impl CanvasWindowManager {
 
         while let Ok((x, y)) = rx.recv().await {
             cx.update_global(|window_manager: &mut Self, cx| {
-                for window in window_manager.windows.values() {
-                    window.on_mouse_move(cx, x, y);
+                for (display_id, window) in &window_manager.windows {
+                    if let Some(display) = window_manager.displays.get(display_id) {
+                        window.on_mouse_move(cx, display, x, y);
+                    }
                 }
             })
             .unwrap();
         }
     }
 
-    fn setup_canvas_windows(cx: &mut App) -> HashMap<DisplayId, CanvasWindow> {
+    fn setup_canvas_windows(
+        cx: &mut App,
+        displays: &HashMap<DisplayId, Display>,
+    ) -> HashMap<DisplayId, CanvasWindow> {
         let mut windows = HashMap::new();
 
-        for display in get_displays().unwrap() {
-            if display.is_mirrored {
+        for display in displays.values() {
+            if display.is_mirrored || !Settings::global(cx).is_display_enabled(&display.id) {
                 continue;
             };
 
-            windows.insert(display.id.clone(), CanvasWindow::new(cx, display));
+            windows.insert(display.id.clone(), CanvasWindow::new(cx, display.clone()));
         }
 
         windows
@@ -81,25 +111,61 @@ impl CanvasWindowManager {
             cx.update_global(|this: &mut Self, cx| match event {
                 DisplayEvent::Added(display) => {
                     let id = display.id.clone();
-                    let window = CanvasWindow::new(cx, display);
-                    this.windows.insert(id, window);
+                    this.displays.insert(id.clone(), display.clone());
+
+                    if Settings::global(cx).is_display_enabled(&id) {
+                        let window = CanvasWindow::new(cx, display);
+                        this.windows.insert(id, window);
+                    }
                 }
                 DisplayEvent::Removed(display_id) => {
+                    this.displays.remove(&display_id);
+
                     if let Some(window) = this.windows.remove(&display_id) {
                         window.close(cx);
                     }
                 }
                 DisplayEvent::SizeChanged { display, after, .. } => {
-                    if let Some(window) = this.windows.get(&display.id) {
+                    if let Some(known) = this.displays.get_mut(&display.id) {
+                        known.size = after;
+                    }
+
+                    if let Some(window) = this.windows.get_mut(&display.id) {
                         window.set_size(cx, after);
                     }
                 }
+                DisplayEvent::OriginChanged { display, after, .. } => {
+                    if let Some(known) = this.displays.get_mut(&display.id) {
+                        known.origin = after;
+                    }
+
+                    if let Some(window) = this.windows.get_mut(&display.id) {
+                        window.set_origin(cx, after);
+                    }
+                }
+                DisplayEvent::ScaleFactorChanged { display, .. } => {
+                    // The logical origin and size of the display depend on the scale factor.
+                    if let Some(window) = this.windows.get_mut(&display.id) {
+                        window.set_origin(cx, display.origin);
+                        window.set_size(cx, display.size);
+                    }
+
+                    this.displays.insert(display.id.clone(), display);
+                }
                 DisplayEvent::Mirrored(display) => {
+                    if let Some(known) = this.displays.get_mut(&display.id) {
+                        known.is_mirrored = true;
+                    }
+
                     if let Some(window) = this.windows.get(&display.id) {
                         window.set_hidden(cx, true);
                     }
                 }
                 DisplayEvent::UnMirrored(display) => {
+                    if let Some(known) = this.displays.get_mut(&display.id) {
+                        known.is_mirrored = false;
+                    }
+
                     if let Some(window) = this.windows.get(&display.id) {
                         window.set_hidden(cx, false);
                     }
@@ -110,6 +176,276 @@ impl CanvasWindowManager {
         }
     }
 
+    /// Get the known displays ordered by their position.
+    pub fn displays(&self) -> Vec<&Display> {
+        let mut displays: Vec<_> = self.displays.values().collect();
+        displays.sort_by_key(|display| (display.origin.x, display.origin.y));
+        displays
+    }
+
+    pub fn display(&self, display_id: &DisplayId) -> Option<&Display> {
+        self.displays.get(display_id)
+    }
+
+    pub fn canvas_window(&self, display_id: &DisplayId) -> Option<&CanvasWindow> {
+        self.windows.get(display_id)
+    }
+
+    /// Enable or disable the canvas window on the display and remember it.
+    pub fn set_display_enabled(&mut self, cx: &mut App, display_id: &DisplayId, enabled: bool) {
+        Settings::update(cx, |settings| {
+            settings.set_display_enabled(display_id, enabled);
+        });
+
+        self.set_window_open(cx, display_id, enabled);
+    }
+
+    /// Cover only the displays with the keys while running, without changing the settings file.
+    pub fn cover_only(&mut self, cx: &mut App, display_keys: BTreeSet<String>) {
+        Settings::update_global(cx, |settings, _| {
+            settings.displays.only = Some(display_keys)
+        });
+
+        let display_ids: Vec<_> = self.displays.keys().cloned().collect();
+        for display_id in display_ids {
+            let enabled = Settings::global(cx).is_display_enabled(&display_id);
+            self.set_window_open(cx, &display_id, enabled);
+        }
+    }
+
+    fn set_window_open(&mut self, cx: &mut App, display_id: &DisplayId, open: bool) {
+        if open {
+            if self.windows.contains_key(display_id) {
+                return;
+            }
+
+            if let Some(display) = self.displays.get(display_id)
+                && !display.is_mirrored
+            {
+                let window = CanvasWindow::new(cx, display.clone());
+                self.windows.insert(display_id.clone(), window);
+            }
+        } else if let Some(window) = self.windows.remove(display_id) {
+            window.close(cx);
+        }
+    }
+
+    pub fn is_selecting_region(&self) -> bool {
+        !self.region_windows.is_empty()
+    }
+
+    /// Show the region windows over the canvases to select the region which each canvas covers.
+    pub fn start_region_selection(&mut self, cx: &mut App) {
+        if self.is_selecting_region() {
+            return;
+        }
+
+        for window in self.windows.values() {
+            if let Some(display) = self.displays.get(window.display_id()) {
+                let region_window = RegionWindow::new(cx, display, window.region());
+                self.region_windows.push(region_window);
+            }
+        }
+    }
+
+    pub fn cancel_region_selection(&mut self, cx: &mut App) {
+        for region_window in self.region_windows.drain(..) {
+            region_window.close(cx);
+        }
+    }
+
+    pub fn finish_region_selection(
+        &mut self,
+        cx: &mut App,
+        display_id: &DisplayId,
+        region: Option<Bounds<Pixels>>,
+    ) {
+        self.cancel_region_selection(cx);
+
+        if let Some(window) = self.windows.get_mut(display_id) {
+            window.set_region(cx, region);
+        }
+    }
+
+    /// Get the IDs of the application windows which canvases are pinned to.
+    pub fn pinned_window_ids(&self) -> Vec<u64> {
+        self.windows
+            .values()
+            .filter_map(CanvasWindow::pinned_window_id)
+            .collect()
+    }
+
+    /// Find the display which contains the center of the target window.
+    fn display_of_target(&self, target: &TargetWindow) -> Option<&Display> {
+        let displays = self
+            .displays
+            .values()
+            .filter(|display| self.windows.contains_key(&display.id));
+
+        displays
+            .clone()
+            .find(|display| {
+                let origin = target.origin.to_logical::<f64>(display.scale_factor);
+                let size = target.size.to_logical::<f64>(display.scale_factor);
+                let (x, y) = (origin.x + size.width / 2., origin.y + size.height / 2.);
+
+                let left = display.origin.x as f64;
+                let top = display.origin.y as f64;
+                (left..left + display.size.width as f64).contains(&x)
+                    && (top..top + display.size.height as f64).contains(&y)
+            })
+            .or_else(|| displays.min_by_key(|display| !display.is_primary))
+    }
+
+    /// Pin the canvas of the display where the target window is to the target window.
+    pub fn pin_to_window(&mut self, cx: &mut App, target: &TargetWindow) {
+        let Some(display) = self.display_of_target(target) else {
+            return;
+        };
+        let display_id = display.id.clone();
+        let scale_factor = display.scale_factor;
+
+        let (tx, rx) = async_channel::unbounded();
+        let tracker = match platform_impl::track_target_window(target.id, move |event| {
+            _ = tx.send_blocking(event);
+        }) {
+            Ok(tracker) => tracker,
+            Err(error) => {
+                eprintln!("Failed to track the window `{}`: {error:#}", target.title);
+                return;
+            }
+        };
+
+        if let Some(window) = self.windows.get_mut(&display_id) {
+            window.pin_to_window(cx, target, scale_factor, tracker);
+        }
+
+        let target_id = target.id;
+        cx.spawn(async move |cx| Self::target_window_listener(cx, rx, display_id, target_id).await)
+            .detach();
+    }
+
+    async fn target_window_listener(
+        cx: &mut AsyncApp,
+        rx: async_channel::Receiver<TargetWindowEvent>,
+        display_id: DisplayId,
+        target_id: u64,
+    ) {
+        // The channel is closed when the tracker is dropped by unpinning.
+        while let Ok(event) = rx.recv().await {
+            let result = cx.update_global(|this: &mut Self, cx| {
+                if let Some(window) = this.windows.get_mut(&display_id) {
+                    window.on_target_window_event(cx, target_id, event);
+                }
+            });
+
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Make the canvas pinned to the application window cover its display again.
+    pub fn unpin_window(&mut self, cx: &mut App, target_id: u64) {
+        for window in self.windows.values_mut() {
+            if window.pinned_window_id() == Some(target_id) {
+                window.set_region(cx, None);
+            }
+        }
+    }
+
+    /// Start or stop listening to the clicks to follow the settings.
+    fn update_click_listener(&mut self, cx: &mut App) {
+        if !Settings::global(cx).ripple.enabled {
+            self.click_listener = None;
+            return;
+        }
+
+        if self.click_listener.is_some() {
+            return;
+        }
+
+        let (tx, rx) = async_channel::unbounded();
+        match platform_impl::listen_clicks(move |click| {
+            _ = tx.send_blocking(click);
+        }) {
+            Ok(listener) => self.click_listener = Some(listener),
+            Err(error) => {
+                eprintln!("Failed to listen to the clicks: {error:#}");
+                return;
+            }
+        }
+
+        cx.spawn(async move |cx| Self::click_listener(cx, rx).await)
+            .detach();
+    }
+
+    async fn click_listener(cx: &mut AsyncApp, rx: async_channel::Receiver<MouseClick>) {
+        // The channel is closed when the listener is dropped.
+        while let Ok(click) = rx.recv().await {
+            let result = cx.update_global(|this: &mut Self, cx| {
+                for (display_id, window) in &this.windows {
+                    if let Some(display) = this.displays.get(display_id) {
+                        window.on_click(cx, display, click);
+                    }
+                }
+            });
+
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Enable or disable the click ripples and remember it.
+    pub fn set_click_ripples_enabled(&mut self, cx: &mut App, enabled: bool) {
+        Settings::update(cx, |settings| settings.ripple.enabled = enabled);
+        self.update_click_listener(cx);
+    }
+
+    /// Start or stop taking the wheel turned with the modifier to follow the tool.
+    pub fn update_wheel_listener(&mut self, cx: &mut App, tool: Tool) {
+        if !tool.canvas_tool().takes_wheel() {
+            self.wheel_listener = None;
+            return;
+        }
+
+        if self.wheel_listener.is_some() {
+            return;
+        }
+
+        let (tx, rx) = async_channel::unbounded();
+        match platform_impl::listen_wheel(move |wheel| {
+            _ = tx.send_blocking(wheel);
+        }) {
+            Ok(listener) => self.wheel_listener = Some(listener),
+            Err(error) => {
+                eprintln!("Failed to listen to the mouse wheel: {error:#}");
+                return;
+            }
+        }
+
+        cx.spawn(async move |cx| Self::wheel_listener(cx, rx).await)
+            .detach();
+    }
+
+    async fn wheel_listener(cx: &mut AsyncApp, rx: async_channel::Receiver<WheelEvent>) {
+        // The channel is closed when the listener is dropped.
+        while let Ok(wheel) = rx.recv().await {
+            let result = cx.update_global(|this: &mut Self, cx| {
+                for (display_id, window) in &this.windows {
+                    if let Some(display) = this.displays.get(display_id) {
+                        window.on_wheel(cx, display, wheel);
+                    }
+                }
+            });
+
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+
     pub fn set_action_mode(&self, cx: &mut App, action_mode: bool) {
         for window in self.windows.values() {
             window.set_ignore_cursor_events(cx, action_mode);
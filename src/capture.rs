@@ -0,0 +1,181 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context as _;
+use display_config::{Display, DisplayId};
+use gpui::{App, ClipboardItem, Image, ImageFormat, ReadGlobal, UpdateGlobal};
+
+use crate::{
+    canvas::CanvasSticker, canvas_orchestrator::CanvasOrchestrator,
+    canvas_window_manager::CanvasWindowManager, platform_impl,
+};
+
+/// Where the captured image goes.
+#[derive(Clone, Copy, Debug)]
+pub enum CaptureTarget {
+    Clipboard,
+    /// Save it as a PNG file in the pictures directory.
+    File,
+}
+
+/// Capture the display where the cursor was last, together with the strokes on its canvas.
+pub fn capture_display(cx: &mut App, target: CaptureTarget) {
+    let manager = CanvasWindowManager::global(cx);
+    let display = CanvasOrchestrator::global(cx)
+        .cursor_display()
+        .and_then(|display_id| manager.display(display_id))
+        .or_else(|| {
+            manager
+                .displays()
+                .into_iter()
+                .find(|display| display.is_primary)
+        })
+        .cloned();
+
+    let Some(display) = display else {
+        eprintln!("There is no display to capture");
+        return;
+    };
+
+    // Hide the canvas window so that it is not captured. The strokes are drawn afterwards
+    // at the display's scale instead.
+    set_canvas_hidden(cx, &display.id, true);
+
+    cx.spawn(async move |cx| {
+        // Wait until the canvas window disappears from the screen.
+        cx.background_executor()
+            .timer(Duration::from_millis(200))
+            .await;
+
+        let captured = platform_impl::capture_screen(
+            display.origin.cast(),
+            display.size.cast(),
+            display.scale_factor,
+        );
+
+        _ = cx.update(|cx| {
+            set_canvas_hidden(cx, &display.id, false);
+
+            let result = captured.and_then(|captured| compose(cx, &display, captured));
+            if let Err(error) = result.and_then(|png| write(cx, target, png)) {
+                eprintln!("Failed to capture the display: {error:#}");
+            }
+        });
+    })
+    .detach();
+}
+
+fn set_canvas_hidden(cx: &mut App, display_id: &DisplayId, hidden: bool) {
+    CanvasWindowManager::update_global(cx, |manager, cx| {
+        if let Some(window) = manager.canvas_window(display_id) {
+            window.set_hidden(cx, hidden);
+        }
+    });
+}
+
+/// Draw the strokes on the captured image and encode it as PNG.
+fn compose(
+    cx: &mut App,
+    display: &Display,
+    captured: platform_impl::CapturedImage,
+) -> anyhow::Result<Vec<u8>> {
+    let size = tiny_skia::IntSize::from_wh(captured.width, captured.height)
+        .context("The captured image is empty")?;
+    // macOS captures at the resolution of the screen rather than the scale factor of the display,
+    // so the strokes are scaled to fit the image.
+    let scale = captured.width as f32 / display.size.width as f32;
+
+    // `tiny_skia` uses RGBA, and the alpha of the captured image is always opaque.
+    let mut data = captured.data;
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    let mut pixmap =
+        tiny_skia::Pixmap::from_vec(data, size).context("The captured image is broken")?;
+
+    let Some(window) = CanvasWindowManager::global(cx).canvas_window(&display.id) else {
+        return pixmap.encode_png().map_err(Into::into);
+    };
+
+    // The strokes are relative to the canvas window, which may cover only a part of the display.
+    let (origin, _) = window.window_rect();
+    let transform = tiny_skia::Transform::from_scale(scale, scale).pre_translate(
+        (origin.x - display.origin.x) as f32,
+        (origin.y - display.origin.y) as f32,
+    );
+
+    let mut paths = Vec::new();
+    let mut stickers = Vec::new();
+    CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+        orchestrator.update_canvas(cx, &display.id, |canvas, _| {
+            if !canvas.is_hidden() {
+                paths = canvas.visible_paths();
+                stickers = canvas.visible_stickers();
+            }
+        });
+    });
+
+    for sticker in stickers {
+        draw_sticker(&mut pixmap, transform, &sticker);
+    }
+
+    for path in paths {
+        path.rasterize(&mut pixmap, transform);
+    }
+
+    pixmap.encode_png().map_err(Into::into)
+}
+
+fn draw_sticker(
+    pixmap: &mut tiny_skia::Pixmap,
+    transform: tiny_skia::Transform,
+    sticker: &CanvasSticker,
+) {
+    let size = sticker.image.size(0);
+    let Some(mut image) = tiny_skia::Pixmap::new(size.width.0 as u32, size.height.0 as u32) else {
+        return;
+    };
+
+    // The sticker is straight BGRA while `tiny_skia` uses premultiplied RGBA.
+    let data = sticker.image.as_bytes(0).unwrap_or_default();
+    for (pixel, bgra) in image.pixels_mut().iter_mut().zip(data.chunks_exact(4)) {
+        *pixel = tiny_skia::ColorU8::from_rgba(bgra[2], bgra[1], bgra[0], bgra[3]).premultiply();
+    }
+
+    let bounds = sticker.bounds;
+    let transform = transform
+        .pre_translate(f32::from(bounds.origin.x), f32::from(bounds.origin.y))
+        .pre_scale(
+            f32::from(bounds.size.width) / image.width() as f32,
+            f32::from(bounds.size.height) / image.height() as f32,
+        );
+    let paint = tiny_skia::PixmapPaint {
+        quality: tiny_skia::FilterQuality::Bilinear,
+        ..Default::default()
+    };
+
+    pixmap.draw_pixmap(0, 0, image.as_ref(), &paint, transform, None);
+}
+
+fn write(cx: &mut App, target: CaptureTarget, png: Vec<u8>) -> anyhow::Result<()> {
+    match target {
+        CaptureTarget::Clipboard => {
+            let image = Image::from_bytes(ImageFormat::Png, png);
+            cx.write_to_clipboard(ClipboardItem::new_image(&image));
+        }
+        CaptureTarget::File => {
+            let dir = dirs::picture_dir()
+                .or_else(dirs::home_dir)
+                .context("Could not find the pictures directory")?;
+            let timestamp = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let path = dir.join(format!("monica-{timestamp}.png"));
+
+            std::fs::write(&path, png)
+                .with_context(|| format!("Failed to write `{}`", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
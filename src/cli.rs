@@ -0,0 +1,223 @@
+use std::{collections::BTreeSet, ffi::OsString, path::PathBuf};
+
+use gpui::{Hsla, Rgba};
+use serde::Deserialize;
+
+use crate::{canvas_tool::Tool, settings::ToolSettings, utils};
+
+/// The help, where `{TOOLS}` is replaced with the names of the tools.
+const HELP: &str = "\
+Monica - Simple, cross-platform on-screen annotation software
+
+Usage: monica [OPTIONS]
+
+Options:
+      --tool <TOOL>      The tool chosen at startup
+                         [{TOOLS}]
+      --color <COLOR>    The color of the pen such as `#ff0000`
+      --width <WIDTH>    The width of the pen strokes
+      --display <NAME>   Cover only this display, given by its name or its number from 1
+                         in the display list. It can be given more than once
+      --hidden           Start with the controller window hidden
+      --session <FILE>   Load the annotations from the file and save them to it on exit
+  -h, --help             Print the help
+  -V, --version          Print the version
+";
+
+/// The startup options given on the command line.
+/// They override the settings only while the application is running.
+#[derive(Default)]
+pub struct Cli {
+    pub tool: Option<Tool>,
+    pub color: Option<Hsla>,
+    pub width: Option<f32>,
+    pub displays: Vec<String>,
+    pub hidden: bool,
+    pub session: Option<PathBuf>,
+}
+
+impl Cli {
+    /// Parse the arguments of this process.
+    /// The help, the version and the invalid arguments are printed and the process exits.
+    pub fn from_env() -> Self {
+        let args: Vec<_> = std::env::args_os().skip(1).collect();
+
+        if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+            print!("{}", HELP.replace("{TOOLS}", &tool_names().join(", ")));
+            std::process::exit(0);
+        }
+
+        if args.iter().any(|arg| arg == "-V" || arg == "--version") {
+            println!("monica {}", env!("CARGO_PKG_VERSION"));
+            std::process::exit(0);
+        }
+
+        Self::parse(args).unwrap_or_else(|error| exit_with_usage_error(error))
+    }
+
+    pub fn parse(args: Vec<OsString>) -> anyhow::Result<Self> {
+        let mut args = pico_args::Arguments::from_vec(args);
+
+        let cli = Self {
+            tool: args.opt_value_from_fn("--tool", parse_tool)?,
+            color: args.opt_value_from_fn("--color", parse_color)?,
+            width: args.opt_value_from_fn("--width", parse_width)?,
+            displays: args.values_from_str("--display")?,
+            hidden: args.contains("--hidden"),
+            session: args
+                .opt_value_from_os_str("--session", |path| anyhow::Ok(PathBuf::from(path)))?,
+        };
+
+        let rest = args.finish();
+        if let Some(arg) = rest.first() {
+            anyhow::bail!("Unexpected argument `{}`", arg.to_string_lossy());
+        }
+
+        Ok(cli)
+    }
+}
+
+/// Print the error in the arguments and exit with the status for the wrong usage.
+pub fn exit_with_usage_error(error: anyhow::Error) -> ! {
+    eprintln!("{error:#}\n\nFor more information, try `--help`.");
+    std::process::exit(2);
+}
+
+/// The names of the tools in the order of the toolbar, which are the same as the settings file.
+fn tool_names() -> Vec<String> {
+    Tool::ALL
+        .iter()
+        .filter_map(|tool| match serde_json::to_value(tool) {
+            Ok(serde_json::Value::String(name)) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+fn parse_tool(value: &str) -> anyhow::Result<Tool> {
+    // Use the same names as the settings file.
+    let deserializer = serde::de::value::StrDeserializer::<serde::de::value::Error>::new(value);
+    Tool::deserialize(deserializer).map_err(|_| anyhow::anyhow!("Unknown tool `{value}`"))
+}
+
+pub fn parse_color(value: &str) -> anyhow::Result<Hsla> {
+    Ok(Rgba::try_from(value)?.into())
+}
+
+fn parse_width(value: &str) -> anyhow::Result<f32> {
+    let width: f32 = value.parse()?;
+    let range = ToolSettings::PEN_WIDTH_RANGE;
+
+    if !range.contains(&width) {
+        anyhow::bail!(
+            "The width should be between {} and {}",
+            range.start(),
+            range.end()
+        );
+    }
+
+    Ok(width)
+}
+
+/// Get the keys of the displays given with `--display`.
+/// The numbers follow the order of the display list of the controller window.
+pub fn select_displays(selectors: &[String]) -> anyhow::Result<BTreeSet<String>> {
+    let mut displays = display_config::get_displays().unwrap_or_default();
+    displays.sort_by_key(|display| (display.origin.x, display.origin.y));
+
+    let names: Vec<_> = displays
+        .iter()
+        .map(|display| display.name.as_str())
+        .collect();
+
+    selectors
+        .iter()
+        .map(|selector| {
+            let index = find_display(&names, selector)
+                .ok_or_else(|| anyhow::anyhow!("Could not find the display `{selector}`"))?;
+            Ok(utils::display_key(&displays[index].id))
+        })
+        .collect()
+}
+
+/// Find the display by the number from 1 or the name in any case.
+fn find_display(names: &[&str], selector: &str) -> Option<usize> {
+    match selector.parse::<usize>() {
+        Ok(number) => (1..=names.len()).contains(&number).then(|| number - 1),
+        Err(_) => names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(selector)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> anyhow::Result<Cli> {
+        Cli::parse(args.iter().map(OsString::from).collect())
+    }
+
+    #[test]
+    fn parse_arguments() {
+        let cli = parse(&[
+            "--tool",
+            "pen",
+            "--color",
+            "#ff0000",
+            "--width",
+            "4",
+            "--display",
+            "2",
+            "--display",
+            "DELL U2720Q",
+            "--hidden",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.tool, Some(Tool::Pen));
+        assert_eq!(cli.color, Some(parse_color("#ff0000").unwrap()));
+        assert_eq!(cli.width, Some(4.));
+        assert_eq!(cli.displays, ["2", "DELL U2720Q"]);
+        assert!(cli.hidden);
+        assert_eq!(cli.session, None);
+
+        let cli = parse(&[]).unwrap();
+        assert_eq!(cli.tool, None);
+        assert!(cli.displays.is_empty());
+        assert!(!cli.hidden);
+    }
+
+    #[test]
+    fn reject_invalid_arguments() {
+        assert!(parse(&["--tool", "brush"]).is_err());
+        assert!(parse(&["--color", "red-ish"]).is_err());
+        assert!(parse(&["--width", "0"]).is_err());
+        assert!(parse(&["--width"]).is_err());
+        assert!(parse(&["--unknown"]).is_err());
+    }
+
+    #[test]
+    fn list_tool_names() {
+        let names = tool_names();
+        assert_eq!(names.len(), Tool::ALL.len());
+        assert_eq!(names[..2], ["cursor", "pen"]);
+
+        for (name, tool) in names.iter().zip(Tool::ALL) {
+            assert_eq!(parse_tool(name).unwrap(), *tool);
+        }
+    }
+
+    #[test]
+    fn find_display_by_number_or_name() {
+        let names = ["Built-in Display", "DELL U2720Q"];
+
+        assert_eq!(find_display(&names, "1"), Some(0));
+        assert_eq!(find_display(&names, "2"), Some(1));
+        assert_eq!(find_display(&names, "dell u2720q"), Some(1));
+
+        assert_eq!(find_display(&names, "0"), None);
+        assert_eq!(find_display(&names, "3"), None);
+        assert_eq!(find_display(&names, "Projector"), None);
+    }
+}
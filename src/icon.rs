@@ -3,6 +3,7 @@ use anyhow::Context as _;
 #[derive(rust_embed::RustEmbed)]
 #[folder = "./assets"]
 #[include = "icons/**/*.svg"]
+#[include = "stickers/**/*"]
 pub struct Assets;
 
 impl gpui::AssetSource for Assets {
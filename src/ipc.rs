@@ -0,0 +1,562 @@
+use std::{
+    ffi::OsString,
+    io::{BufRead, BufReader, Read, Write},
+    path::{Component, Path, PathBuf},
+    sync::mpsc,
+};
+
+use anyhow::Context as _;
+use gpui::{App, AsyncApp, UpdateGlobal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::ToolState,
+    canvas_orchestrator::CanvasOrchestrator,
+    canvas_tool::Tool,
+    canvas_window_manager::CanvasWindowManager,
+    cli::{self, Cli},
+    main_window::MainWindow,
+    session::{self, SessionFile},
+};
+
+/// The command sent to the running instance as a line of JSON,
+/// e.g. `{"command": "set_tool", "tool": "pen"}`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    SetTool {
+        tool: Tool,
+    },
+    /// The color such as `#ff0000`.
+    SetColor {
+        color: String,
+    },
+    Undo,
+    Redo,
+    Clear,
+    /// Save the annotations to the file named `file_name`, or to the session file if it is
+    /// not given. The file is put beside the session file, or in the pictures directory.
+    Save {
+        #[serde(default)]
+        file_name: Option<String>,
+    },
+    /// Hide or show the annotations without clearing them.
+    ToggleVisibility,
+    /// The command-line arguments given to another invocation of Monica.
+    Arguments {
+        args: Vec<String>,
+        /// The working directory which the relative paths in `args` are based on.
+        #[serde(default)]
+        cwd: Option<PathBuf>,
+    },
+}
+
+/// The reply to a command as a line of JSON, e.g. `{"ok": true}`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Reply {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<anyhow::Result<()>> for Reply {
+    fn from(result: anyhow::Result<()>) -> Self {
+        Self {
+            ok: result.is_ok(),
+            error: result.err().map(|error| format!("{error:#}")),
+        }
+    }
+}
+
+pub use platform::Listener;
+
+type Request = (Command, mpsc::Sender<Reply>);
+
+/// Listen to the commands from other programs on the control socket,
+/// and run them on the main thread.
+pub fn start_server(cx: &mut App, mut listener: Listener) {
+    let (tx, rx) = async_channel::unbounded();
+    std::thread::spawn(move || {
+        while !tx.is_closed() {
+            let stream = match listener.accept() {
+                Ok(stream) => stream,
+                Err(error) => {
+                    eprintln!("Failed to accept the control connection: {error:#}");
+                    continue;
+                }
+            };
+
+            // A client which keeps the connection open does not block the others.
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                if let Err(error) = handle_connection(&stream, &stream, &tx) {
+                    eprintln!("The control connection is closed: {error:#}");
+                }
+            });
+        }
+    });
+
+    cx.spawn(async move |cx| run_requests(cx, rx).await)
+        .detach();
+}
+
+async fn run_requests(cx: &mut AsyncApp, rx: async_channel::Receiver<Request>) {
+    while let Ok((command, reply_tx)) = rx.recv().await {
+        let Ok(result) = cx.update(|cx| run(cx, command)) else {
+            break;
+        };
+
+        _ = reply_tx.send(result.into());
+    }
+}
+
+fn handle_connection(
+    reader: impl Read,
+    mut writer: impl Write,
+    tx: &async_channel::Sender<Request>,
+) -> anyhow::Result<()> {
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match serde_json::from_str(&line) {
+            Ok(command) => {
+                let (reply_tx, reply_rx) = mpsc::channel();
+                tx.send_blocking((command, reply_tx))?;
+                reply_rx.recv()?
+            }
+            Err(error) => Err(anyhow::anyhow!("Invalid command: {error}")).into(),
+        };
+
+        let mut reply = serde_json::to_string(&reply)?;
+        reply.push('\n');
+        writer.write_all(reply.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Send the command to the running instance.
+/// Returns `None` if there is no running instance.
+pub fn send(command: &Command) -> anyhow::Result<Option<Reply>> {
+    let Some(stream) = platform::connect() else {
+        return Ok(None);
+    };
+
+    let mut line = serde_json::to_string(command)?;
+    line.push('\n');
+    (&stream).write_all(line.as_bytes())?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+
+    let reply = serde_json::from_str(&reply).context("The running instance replied wrongly")?;
+    Ok(Some(reply))
+}
+
+fn run(cx: &mut App, command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::SetTool { tool } => {
+            ToolState::update_global(cx, |state, cx| state.set_tool(cx, tool));
+        }
+        Command::SetColor { color } => {
+            let color = cli::parse_color(&color)?;
+            ToolState::update_global(cx, |state, _| state.color = color);
+        }
+        Command::Undo => CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+            orchestrator.undo(cx);
+        }),
+        Command::Redo => CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+            orchestrator.redo(cx);
+        }),
+        Command::Clear => CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+            orchestrator.clear(cx);
+        }),
+        Command::Save { file_name } => {
+            let path = match file_name {
+                Some(file_name) => save_path(cx, &file_name)?,
+                None => cx
+                    .try_global::<SessionFile>()
+                    .map(|session| session.path().to_owned())
+                    .context("No path is given and Monica is not started with `--session`")?,
+            };
+
+            session::save(cx, &path)?;
+        }
+        Command::ToggleVisibility => CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+            orchestrator.toggle_annotations_hidden(cx);
+        }),
+        Command::Arguments { args, cwd } => {
+            let mut cli = Cli::parse(args.into_iter().map(OsString::from).collect())?;
+            if let Some(cwd) = cwd {
+                cli.session = cli.session.map(|path| cwd.join(path));
+            }
+
+            apply_arguments(cx, cli)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the path to save the annotations to the file named `file_name`.
+///
+/// Only a plain file name is accepted, so that the other programs can not overwrite
+/// an arbitrary file through the control socket.
+fn save_path(cx: &App, file_name: &str) -> anyhow::Result<PathBuf> {
+    let mut components = Path::new(file_name).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        anyhow::bail!("`{file_name}` is not a file name");
+    }
+
+    let dir = match cx.try_global::<SessionFile>() {
+        Some(session) => session.path().parent().map(Path::to_owned),
+        None => dirs::picture_dir().or_else(dirs::home_dir),
+    };
+    let dir = dir.context("Could not find the directory to save the annotations to")?;
+
+    Ok(dir.join(file_name))
+}
+
+/// Check `--session` given by another invocation, which may only name the current session.
+///
+/// The session file is saved on exit, so taking a new one from the control socket
+/// would let the other programs overwrite an arbitrary file, see `save_path`.
+fn check_session(current: Option<&Path>, path: &Path) -> anyhow::Result<()> {
+    match current {
+        Some(current) if current == path => Ok(()),
+        Some(_) => anyhow::bail!("The session can not be changed while Monica is running"),
+        None => anyhow::bail!("The session can only be given when Monica starts"),
+    }
+}
+
+/// Apply the command-line options of another invocation to this instance.
+fn apply_arguments(cx: &mut App, cli: Cli) -> anyhow::Result<()> {
+    if let Some(path) = cli.session {
+        let current = cx.try_global::<SessionFile>().map(SessionFile::path);
+        check_session(current, &path)?;
+    }
+
+    ToolState::update_global(cx, |state, cx| {
+        if let Some(color) = cli.color {
+            state.color = color;
+        }
+
+        if let Some(width) = cli.width {
+            state.pen_width = width;
+        }
+
+        if let Some(tool) = cli.tool {
+            state.set_tool(cx, tool);
+        }
+    });
+
+    if !cli.displays.is_empty() {
+        let display_keys = cli::select_displays(&cli.displays)?;
+        CanvasWindowManager::update_global(cx, |manager, cx| {
+            manager.cover_only(cx, display_keys);
+        });
+    }
+
+    // Another launch of Monica shows the running instance instead of starting again.
+    MainWindow::update_global(cx, |window, cx| {
+        if cli.hidden {
+            window.set_hidden(cx, true);
+        } else {
+            window.show(cx);
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::{
+        fs::DirBuilder,
+        io::ErrorKind,
+        os::unix::{
+            fs::{DirBuilderExt, MetadataExt},
+            net::{UnixListener, UnixStream},
+        },
+        path::PathBuf,
+    };
+
+    use anyhow::Context as _;
+
+    unsafe extern "C" {
+        fn getuid() -> u32;
+    }
+
+    /// Get the directory which only the current user can access.
+    fn socket_dir() -> anyhow::Result<PathBuf> {
+        if let Some(dir) = dirs::runtime_dir() {
+            return Ok(dir);
+        }
+
+        // Anyone can write to the temporary directory, so make our own in it.
+        let uid = unsafe { getuid() };
+        let dir = std::env::temp_dir().join(format!("{}-{uid}", crate::APP_IDENTIFIER));
+        match DirBuilder::new().mode(0o700).create(&dir) {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => {}
+            Err(error) => {
+                return Err(error).with_context(|| format!("Failed to create `{}`", dir.display()));
+            }
+        }
+
+        // The directory may be made by another user before us.
+        let metadata = std::fs::symlink_metadata(&dir)?;
+        if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+            anyhow::bail!("`{}` is not private to the current user", dir.display());
+        }
+
+        Ok(dir)
+    }
+
+    fn socket_path() -> anyhow::Result<PathBuf> {
+        Ok(socket_dir()?.join(format!("{}.sock", crate::APP_IDENTIFIER)))
+    }
+
+    pub struct Listener(UnixListener);
+
+    impl Listener {
+        /// Take the control socket. Returns `None` if another instance is listening on it.
+        pub fn bind() -> anyhow::Result<Option<Self>> {
+            let path = socket_path()?;
+
+            if path.exists() {
+                if UnixStream::connect(&path).is_ok() {
+                    return Ok(None);
+                }
+
+                // The socket is left by the instance which did not exit normally.
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove `{}`", path.display()))?;
+            }
+
+            let listener = UnixListener::bind(&path)
+                .with_context(|| format!("Failed to bind `{}`", path.display()))?;
+
+            Ok(Some(Self(listener)))
+        }
+
+        pub fn accept(&mut self) -> anyhow::Result<UnixStream> {
+            Ok(self.0.accept()?.0)
+        }
+    }
+
+    pub fn connect() -> Option<UnixStream> {
+        UnixStream::connect(socket_path().ok()?).ok()
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::{
+        fs::File,
+        os::windows::io::{AsRawHandle, FromRawHandle},
+    };
+
+    use windows::{
+        Win32::{
+            Foundation::{ERROR_ACCESS_DENIED, ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED, HANDLE},
+            Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX},
+            System::Pipes::{
+                ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_TYPE_BYTE,
+                PIPE_UNLIMITED_INSTANCES, PIPE_WAIT, WaitNamedPipeW,
+            },
+        },
+        core::HSTRING,
+    };
+
+    const BUFFER_SIZE: u32 = 4096;
+    /// How many times the client waits for the busy pipe.
+    const CONNECT_ATTEMPTS: usize = 5;
+    /// How long the client waits for the busy pipe each time, in milliseconds.
+    const CONNECT_TIMEOUT: u32 = 200;
+
+    fn pipe_name() -> String {
+        format!(r"\\.\pipe\{}", crate::APP_IDENTIFIER)
+    }
+
+    fn create_pipe(first: bool) -> windows::core::Result<File> {
+        let mut open_mode = PIPE_ACCESS_DUPLEX;
+        if first {
+            // Fail if another instance has created the pipe.
+            open_mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+        }
+
+        let handle = unsafe {
+            CreateNamedPipeW(
+                &HSTRING::from(pipe_name()),
+                open_mode,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+                PIPE_UNLIMITED_INSTANCES,
+                BUFFER_SIZE,
+                BUFFER_SIZE,
+                0,
+                None,
+            )
+        }?;
+
+        Ok(unsafe { File::from_raw_handle(handle.0 as _) })
+    }
+
+    /// The named pipe, whose new instance is created for each connection.
+    pub struct Listener {
+        next: Option<File>,
+    }
+
+    impl Listener {
+        /// Create the pipe. Returns `None` if another instance has created it.
+        pub fn bind() -> anyhow::Result<Option<Self>> {
+            match create_pipe(true) {
+                Ok(pipe) => Ok(Some(Self { next: Some(pipe) })),
+                Err(error) if error.code() == ERROR_ACCESS_DENIED.to_hresult() => Ok(None),
+                Err(error) => Err(error.into()),
+            }
+        }
+
+        pub fn accept(&mut self) -> anyhow::Result<File> {
+            let pipe = match self.next.take() {
+                Some(pipe) => pipe,
+                None => create_pipe(false)?,
+            };
+
+            let handle = HANDLE(pipe.as_raw_handle() as _);
+            if let Err(error) = unsafe { ConnectNamedPipe(handle, None) }
+                // The client may connect between creating the pipe and waiting for it.
+                && error.code() != ERROR_PIPE_CONNECTED.to_hresult()
+            {
+                return Err(error.into());
+            }
+
+            Ok(pipe)
+        }
+    }
+
+    pub fn connect() -> Option<File> {
+        for _ in 0..CONNECT_ATTEMPTS {
+            match std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(pipe_name())
+            {
+                Ok(pipe) => return Some(pipe),
+                // All instances of the pipe are in use until the server creates the next one.
+                Err(error) if error.raw_os_error() == Some(ERROR_PIPE_BUSY.0 as _) => {
+                    _ = unsafe { WaitNamedPipeW(&HSTRING::from(pipe_name()), CONNECT_TIMEOUT) };
+                }
+                Err(_) => return None,
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(command: &Command) -> Command {
+        let line = serde_json::to_string(command).unwrap();
+        serde_json::from_str(&line).unwrap_or_else(|error| panic!("{line}: {error}"))
+    }
+
+    #[test]
+    fn parse_commands() {
+        let command = serde_json::from_str(r#"{"command": "set_tool", "tool": "pen"}"#).unwrap();
+        assert!(matches!(command, Command::SetTool { tool: Tool::Pen }));
+
+        let command = serde_json::from_str(r#"{"command": "toggle_visibility"}"#).unwrap();
+        assert!(matches!(command, Command::ToggleVisibility));
+
+        let command = serde_json::from_str(r#"{"command": "save"}"#).unwrap();
+        assert!(matches!(command, Command::Save { file_name: None }));
+
+        let command = serde_json::from_str(r#"{"command": "arguments", "args": ["--hidden"]}"#);
+        assert!(matches!(
+            command,
+            Ok(Command::Arguments { args, cwd: None }) if args == ["--hidden"]
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_commands() {
+        for line in [
+            r#"{"command": "paint"}"#,
+            r#"{"command": "set_tool", "tool": "brush"}"#,
+            r#"{"command": "set_color"}"#,
+            r#"{"tool": "pen"}"#,
+        ] {
+            assert!(serde_json::from_str::<Command>(line).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn round_trip_commands() {
+        let command = round_trip(&Command::SetColor {
+            color: "#ff0000".to_owned(),
+        });
+        assert!(matches!(command, Command::SetColor { color } if color == "#ff0000"));
+
+        let command = round_trip(&Command::Save {
+            file_name: Some("slides.json".to_owned()),
+        });
+        assert!(matches!(
+            command,
+            Command::Save { file_name: Some(name) } if name == "slides.json"
+        ));
+
+        let command = round_trip(&Command::Arguments {
+            args: vec!["--tool".to_owned(), "spotlight".to_owned()],
+            cwd: Some(PathBuf::from("/home/user")),
+        });
+        assert!(matches!(
+            command,
+            Command::Arguments { args, cwd: Some(cwd) }
+                if args == ["--tool", "spotlight"] && cwd == Path::new("/home/user")
+        ));
+
+        for tool in Tool::ALL {
+            let command = round_trip(&Command::SetTool { tool: *tool });
+            assert!(matches!(command, Command::SetTool { tool: parsed } if parsed == *tool));
+        }
+    }
+
+    #[test]
+    fn check_forwarded_sessions() {
+        let session = Path::new("/home/user/slides.json");
+        assert!(check_session(Some(session), session).is_ok());
+        assert!(check_session(Some(session), Path::new("/home/user/other.json")).is_err());
+
+        // The session given over the socket would be overwritten on exit.
+        let command = serde_json::from_str(
+            r#"{"command": "arguments", "args": ["--session", "/home/user/.bashrc"]}"#,
+        )
+        .unwrap();
+        let Command::Arguments { args, .. } = command else {
+            panic!("{command:?}");
+        };
+        let cli = Cli::parse(args.into_iter().map(OsString::from).collect()).unwrap();
+        assert!(check_session(None, &cli.session.unwrap()).is_err());
+    }
+
+    #[test]
+    fn serialize_replies() {
+        let reply = serde_json::to_string(&Reply::from(Ok(()))).unwrap();
+        assert_eq!(reply, r#"{"ok":true}"#);
+
+        let reply = Reply::from(Err(anyhow::anyhow!("No session")));
+        let reply: Reply = serde_json::from_str(&serde_json::to_string(&reply).unwrap()).unwrap();
+        assert!(!reply.ok);
+        assert_eq!(reply.error.as_deref(), Some("No session"));
+    }
+}
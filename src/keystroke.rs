@@ -0,0 +1,137 @@
+use std::time::{Duration, Instant};
+
+use display_config::DisplayId;
+use gpui::{App, AsyncApp, Global, ReadGlobal};
+
+use crate::{
+    canvas_window_manager::CanvasWindowManager,
+    platform_impl::{self, KeyStroke, Tracker},
+    settings::Settings,
+    utils,
+};
+
+/// The keystrokes shown in the bubble on the display.
+/// They are not the part of the canvas, so they never enter the undo history.
+pub struct KeystrokeDisplay {
+    strokes: Vec<KeyStroke>,
+    last_pressed_at: Option<Instant>,
+    /// It is running while the keystroke display is enabled.
+    key_listener: Option<Tracker>,
+}
+
+impl Global for KeystrokeDisplay {}
+
+impl KeystrokeDisplay {
+    /// The number of the keystrokes shown at once.
+    const MAX_STROKES: usize = 4;
+    /// The time to show the keystrokes after the last key is pressed.
+    const HOLD: Duration = Duration::from_millis(1500);
+    const FADE: Duration = Duration::from_millis(500);
+
+    pub fn register_global(cx: &mut App) {
+        let mut display = Self {
+            strokes: Vec::new(),
+            last_pressed_at: None,
+            key_listener: None,
+        };
+        display.update_key_listener(cx);
+
+        cx.set_global(display);
+    }
+
+    /// Start or stop listening to the keys to follow the settings.
+    fn update_key_listener(&mut self, cx: &mut App) {
+        if !Settings::global(cx).keystroke.enabled {
+            self.key_listener = None;
+            self.strokes.clear();
+            return;
+        }
+
+        if self.key_listener.is_some() {
+            return;
+        }
+
+        let (tx, rx) = async_channel::unbounded();
+        match platform_impl::listen_keys(move |stroke| {
+            _ = tx.send_blocking(stroke);
+        }) {
+            Ok(listener) => self.key_listener = Some(listener),
+            Err(error) => {
+                eprintln!("Failed to listen to the keys: {error:#}");
+                return;
+            }
+        }
+
+        cx.spawn(async move |cx| Self::listener(cx, rx).await)
+            .detach();
+    }
+
+    async fn listener(cx: &mut AsyncApp, rx: async_channel::Receiver<KeyStroke>) {
+        // The channel is closed when the listener is dropped.
+        while let Ok(stroke) = rx.recv().await {
+            let result = cx.update_global(|this: &mut Self, _| this.push(stroke));
+
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+
+    fn push(&mut self, stroke: KeyStroke) {
+        // Start a new bubble if the previous one has already disappeared.
+        if self.opacity() == 0. {
+            self.strokes.clear();
+        }
+
+        if self.strokes.len() >= Self::MAX_STROKES {
+            self.strokes.remove(0);
+        }
+
+        self.strokes.push(stroke);
+        self.last_pressed_at = Some(Instant::now());
+    }
+
+    /// Enable or disable the keystroke display and remember it.
+    pub fn set_enabled(&mut self, cx: &mut App, enabled: bool) {
+        Settings::update(cx, |settings| settings.keystroke.enabled = enabled);
+        self.update_key_listener(cx);
+    }
+
+    /// Get the text of the bubble, or `None` if the bubble is not shown.
+    pub fn text(&self) -> Option<String> {
+        if self.strokes.is_empty() || self.opacity() == 0. {
+            return None;
+        }
+
+        let strokes: Vec<_> = self.strokes.iter().map(ToString::to_string).collect();
+        Some(strokes.join("   "))
+    }
+
+    /// Get the opacity of the bubble which fades out after the keys are pressed.
+    pub fn opacity(&self) -> f32 {
+        let Some(elapsed) = self.last_pressed_at.map(|at| at.elapsed()) else {
+            return 0.;
+        };
+
+        match elapsed.checked_sub(Self::HOLD) {
+            None => 1.,
+            Some(fading) => 1. - (fading.as_secs_f32() / Self::FADE.as_secs_f32()).min(1.),
+        }
+    }
+
+    /// Whether the bubble is shown on the display.
+    pub fn is_shown_on(cx: &App, display_id: &DisplayId) -> bool {
+        match Settings::global(cx).keystroke.display.as_ref() {
+            Some(key) => *key == utils::display_key(display_id),
+            // The canvas windows are rendered while the manager is being registered.
+            None => cx
+                .try_global::<CanvasWindowManager>()
+                .is_some_and(|manager| {
+                    manager
+                        .displays()
+                        .into_iter()
+                        .any(|display| display.is_primary && display.id == *display_id)
+                }),
+        }
+    }
+}
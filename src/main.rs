@@ -1,37 +1,82 @@
 // Prevents additional console window on Windows in release.
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use gpui::{App, ReadGlobal};
+use std::collections::BTreeSet;
+
+use gpui::{App, ReadGlobal, UpdateGlobal};
 
 use crate::{
-    canvas::{Tool, ToolState},
-    canvas_orchestrator::CanvasOrchestrator,
-    canvas_window_manager::CanvasWindowManager,
-    main_window::MainWindow,
+    canvas::ToolState, canvas_orchestrator::CanvasOrchestrator,
+    canvas_window_manager::CanvasWindowManager, cli::Cli, keystroke::KeystrokeDisplay,
+    main_window::MainWindow, pen::PenInput, session::SessionFile, settings::Settings,
+    settings_window::SettingsWindow, shortcut::Shortcuts, tray::Tray,
 };
 
 mod canvas;
 mod canvas_orchestrator;
+mod canvas_tool;
 mod canvas_window;
 mod canvas_window_manager;
+mod capture;
+mod cli;
 mod icon;
+mod ipc;
+mod keystroke;
 mod main_window;
+mod pen;
 mod platform_impl;
+mod region_window;
+mod session;
+mod settings;
+mod settings_window;
+mod shape_recognition;
+mod shortcut;
+mod sticker;
+mod tray;
 mod ui_canvas;
 mod ui_main;
+mod ui_region;
+mod ui_settings;
+mod ui_tool_options;
 mod utils;
 
 const APP_IDENTIFIER: &str = "jp.tasuren.monica";
 
-fn setup(cx: &mut App) {
+/// `displays` is the keys of the displays given with `--display`, which are all covered if empty.
+fn setup(cx: &mut App, cli: Cli, displays: BTreeSet<String>, listener: Option<ipc::Listener>) {
     gpui_component::init(cx);
 
-    ToolState::register_global(cx, Tool::Cursor, gpui::blue());
+    Settings::register_global(cx);
+    if !displays.is_empty() {
+        Settings::update_global(cx, |settings, _| settings.displays.only = Some(displays));
+    }
+
+    let tools = Settings::global(cx).tools.clone();
+    ToolState::register_global(
+        cx,
+        cli.tool.unwrap_or(tools.default_tool),
+        cli.color.unwrap_or(tools.color),
+        cli.width.unwrap_or(tools.pen_width),
+    );
     CanvasOrchestrator::register_global(cx);
+    KeystrokeDisplay::register_global(cx);
+    PenInput::register_global(cx);
     CanvasWindowManager::register_global(cx);
-    MainWindow::register_global(cx);
+    let hidden = cli.hidden || Settings::global(cx).startup.hidden;
+    MainWindow::register_global(cx, hidden);
+    SettingsWindow::register_global(cx);
+    Tray::register_global(cx);
+    Shortcuts::register_global(cx);
+
+    if let Some(path) = cli.session {
+        SessionFile::register_global(cx, path);
+    }
 
-    // Quit the application when main window is closed.
+    if let Some(listener) = listener {
+        ipc::start_server(cx, listener);
+    }
+
+    // Quit the application when main window is closed, unless it is hidden to the tray.
     cx.on_window_closed(move |cx| {
         let no_main_window = cx
             .windows()
@@ -45,8 +90,63 @@ fn setup(cx: &mut App) {
     .detach();
 }
 
+/// Let the running instance handle the arguments and come to the front
+/// instead of opening another set of canvas windows.
+/// Returns false if there is no running instance.
+fn forward_to_running_instance() -> bool {
+    let args = std::env::args_os()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let cwd = std::env::current_dir().ok();
+
+    match ipc::send(&ipc::Command::Arguments { args, cwd }) {
+        Ok(Some(reply)) => {
+            if let Some(error) = reply.error {
+                eprintln!("{error}");
+                std::process::exit(1);
+            }
+
+            true
+        }
+        Ok(None) => false,
+        Err(error) => {
+            eprintln!("Failed to reach the running instance: {error:#}");
+            false
+        }
+    }
+}
+
 fn main() {
+    let cli = Cli::from_env();
+
+    if forward_to_running_instance() {
+        return;
+    }
+
+    // The running instance looks up the forwarded displays by itself.
+    let displays = cli::select_displays(&cli.displays)
+        .unwrap_or_else(|error| cli::exit_with_usage_error(error));
+
+    let listener = match ipc::Listener::bind() {
+        Ok(Some(listener)) => Some(listener),
+        // Another instance has started since we tried to reach it.
+        Ok(None) => {
+            if forward_to_running_instance() {
+                return;
+            }
+
+            eprintln!("Another instance of Monica is running but does not respond");
+            std::process::exit(1);
+        }
+        // Monica still works without the control socket.
+        Err(error) => {
+            eprintln!("Failed to open the control socket: {error:#}");
+            None
+        }
+    };
+
     gpui::Application::new()
         .with_assets(icon::Assets)
-        .run(setup);
+        .run(move |cx| setup(cx, cli, displays, listener));
 }
@@ -1,24 +1,42 @@
-use gpui::{AnyWindowHandle, App, AppContext, Global, point, px, size};
+use gpui::{
+    AnyWindowHandle, App, AppContext, Bounds, Global, Pixels, ReadGlobal, Size, Window, point, px,
+    size,
+};
 
-use crate::platform_impl::WindowExt;
+use crate::{
+    platform_impl::WindowExt,
+    settings::{Settings, ToolbarLayout},
+    tray::Tray,
+};
 
 pub struct MainWindow(AnyWindowHandle);
 
 impl Global for MainWindow {}
 
 impl MainWindow {
-    pub fn register_global(cx: &mut App) {
-        let window = Self(Self::setup_main_window(cx));
+    /// The size of the main window without any additional panel.
+    pub const SIZE: Size<Pixels> = size(px(480.), px(100.));
+    pub const VERTICAL_SIZE: Size<Pixels> = size(px(100.), px(460.));
+    /// The size of the window showing only the chosen tool.
+    pub const COLLAPSED_SIZE: Size<Pixels> = size(px(150.), px(48.));
+    pub const COLLAPSED_VERTICAL_SIZE: Size<Pixels> = size(px(72.), px(136.));
+    /// How near to the edge of the display the window sticks to it.
+    const SNAP_DISTANCE: Pixels = px(24.);
+
+    /// If `hidden` is true, the window is opened without being shown.
+    pub fn register_global(cx: &mut App, hidden: bool) {
+        let window = Self(Self::setup_main_window(cx, hidden));
         cx.set_global(window);
     }
 
-    fn setup_main_window(cx: &mut App) -> AnyWindowHandle {
+    fn setup_main_window(cx: &mut App, hidden: bool) -> AnyWindowHandle {
         let titlebar = Some(gpui::TitlebarOptions {
             title: Some("Monica - Controller".into()),
             appears_transparent: true,
             traffic_light_position: Some(point(px(12.), px(12.))),
         });
-        let bounds = gpui::Bounds::centered(None, size(px(230.), px(100.)), cx);
+        let bounds = Self::saved_bounds(cx)
+            .unwrap_or_else(|| Bounds::centered(None, Self::base_size(cx), cx));
         let window_bounds = Some(gpui::WindowBounds::Windowed(bounds));
 
         let window_options = gpui::WindowOptions {
@@ -33,12 +51,120 @@ impl MainWindow {
         *cx.open_window(window_options, move |window, cx| {
             window.setup_main_window();
 
-            let app_view = crate::ui_main::AppView::new(cx);
+            if hidden {
+                window.set_hidden(true);
+            }
+
+            // Keep running in the tray instead of quitting if it is configured so.
+            window.on_window_should_close(cx, |window, cx| {
+                if Tray::keeps_running(cx) {
+                    window.set_hidden(true);
+                    return false;
+                }
+
+                true
+            });
+
+            let app_view = crate::ui_main::AppView::new(window, cx);
             cx.new(|cx| gpui_component::Root::new(app_view, window, cx))
         })
         .expect("Failed to open the main window.")
     }
 
+    /// Get the bounds at the position where the window is moved last,
+    /// unless the displays are changed and it is out of them.
+    fn saved_bounds(cx: &App) -> Option<Bounds<Pixels>> {
+        let settings = Settings::global(cx);
+        if !settings.startup.restore_position {
+            return None;
+        }
+
+        let (x, y) = settings.toolbar.position?;
+        let bounds = Bounds::new(point(px(x), px(y)), Self::base_size(cx));
+
+        cx.displays()
+            .iter()
+            .any(|display| display.bounds().intersects(&bounds))
+            .then_some(bounds)
+    }
+
+    /// The size of the window in the layout chosen in the settings, without any additional panel.
+    pub fn base_size(cx: &App) -> Size<Pixels> {
+        let toolbar = &Settings::global(cx).toolbar;
+
+        match (toolbar.layout, toolbar.collapsed) {
+            (ToolbarLayout::Horizontal, false) => Self::SIZE,
+            (ToolbarLayout::Vertical, false) => Self::VERTICAL_SIZE,
+            (ToolbarLayout::Horizontal, true) => Self::COLLAPSED_SIZE,
+            (ToolbarLayout::Vertical, true) => Self::COLLAPSED_VERTICAL_SIZE,
+        }
+    }
+
+    /// Move the window to the edge of the display if it is put near the edge.
+    pub fn snap_to_edge(window: &mut Window, cx: &App) {
+        if !Settings::global(cx).toolbar.snap_to_edges {
+            return;
+        }
+
+        let bounds = window.bounds();
+        let Some(area) = cx
+            .displays()
+            .iter()
+            .map(|display| display.bounds())
+            .find(|area| area.contains(&bounds.center()))
+        else {
+            return;
+        };
+
+        let snap = |start: Pixels, end: Pixels, area_start: Pixels, area_end: Pixels| {
+            if (start - area_start).abs() < Self::SNAP_DISTANCE {
+                area_start
+            } else if (area_end - end).abs() < Self::SNAP_DISTANCE {
+                area_end - (end - start)
+            } else {
+                start
+            }
+        };
+
+        let origin = point(
+            snap(bounds.left(), bounds.right(), area.left(), area.right()),
+            snap(bounds.top(), bounds.bottom(), area.top(), area.bottom()),
+        );
+
+        if origin != bounds.origin {
+            window.set_position(dpi::LogicalPosition::new(
+                f32::from(origin.x) as i32,
+                f32::from(origin.y) as i32,
+            ));
+        }
+    }
+
+    /// Remember the position of the window to restore it at the next startup.
+    pub fn save_position(cx: &mut App, bounds: Bounds<Pixels>) {
+        let position = (f32::from(bounds.origin.x), f32::from(bounds.origin.y));
+
+        if Settings::global(cx).toolbar.position != Some(position) {
+            Settings::update(cx, |settings| settings.toolbar.position = Some(position));
+        }
+    }
+
+    pub fn set_hidden(&self, cx: &mut App, hidden: bool) {
+        self.0
+            .update(cx, |_, window, _| window.set_hidden(hidden))
+            .unwrap();
+    }
+
+    /// Show the window and bring it to the front.
+    pub fn show(&self, cx: &mut App) {
+        cx.activate(true);
+        self.0
+            .update(cx, |_, window, _| {
+                window.set_hidden(false);
+                window.activate_window();
+            })
+            .unwrap();
+    }
+
     pub fn handle(&self) -> AnyWindowHandle {
         self.0
     }
@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+use gpui::{App, AsyncApp, Global, ReadGlobal, UpdateGlobal};
+
+use crate::{
+    canvas::ToolState,
+    canvas_tool::Tool,
+    platform_impl::{self, PenState, Tracker},
+};
+
+/// The latest state of the stylus of a tablet, which gives the pressure to the strokes.
+pub struct PenInput {
+    state: Option<(PenState, Instant)>,
+    /// The tool to go back to when the stylus is turned from the eraser end.
+    tool_before_eraser: Option<Tool>,
+    _listener: Option<Tracker>,
+}
+
+impl Global for PenInput {}
+
+impl PenInput {
+    /// The state older than this is regarded as left by the stylus which is no longer used,
+    /// e.g. the mouse is used instead.
+    const EXPIRY: Duration = Duration::from_millis(100);
+
+    pub fn register_global(cx: &mut App) {
+        let (tx, rx) = async_channel::unbounded();
+        let listener = match platform_impl::listen_pen(move |state| {
+            _ = tx.send_blocking(state);
+        }) {
+            Ok(listener) => Some(listener),
+            Err(error) => {
+                eprintln!("Failed to listen to the pen: {error:#}");
+                None
+            }
+        };
+
+        if listener.is_some() {
+            cx.spawn(async move |cx| Self::listener(cx, rx).await)
+                .detach();
+        }
+
+        cx.set_global(Self {
+            state: None,
+            tool_before_eraser: None,
+            _listener: listener,
+        });
+    }
+
+    async fn listener(cx: &mut AsyncApp, rx: async_channel::Receiver<PenState>) {
+        while let Ok(state) = rx.recv().await {
+            let result = cx.update_global(|this: &mut Self, cx| this.update(cx, state));
+
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+
+    fn update(&mut self, cx: &mut App, state: PenState) {
+        let was_eraser = self.state.is_some_and(|(state, _)| state.eraser);
+        self.state = Some((state, Instant::now()));
+
+        if state.eraser == was_eraser {
+            return;
+        }
+
+        // Use the eraser while the stylus is turned over.
+        let tool = ToolState::global(cx).tool();
+        let new_tool = if state.eraser {
+            let Some(&eraser) = Tool::ALL
+                .iter()
+                .find(|tool| tool.canvas_tool().is_stylus_eraser())
+            else {
+                return;
+            };
+
+            if tool == eraser {
+                return;
+            }
+
+            self.tool_before_eraser = Some(tool);
+            eraser
+        } else {
+            let Some(tool) = self.tool_before_eraser.take() else {
+                return;
+            };
+
+            tool
+        };
+
+        ToolState::update_global(cx, |tool_state, cx| tool_state.set_tool(cx, new_tool));
+    }
+
+    /// Get the state of the stylus if it is being used.
+    pub fn current(&self) -> Option<PenState> {
+        self.state
+            .filter(|(_, updated_at)| updated_at.elapsed() < Self::EXPIRY)
+            .map(|(state, _)| state)
+    }
+}
@@ -1,3 +1,22 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+#[cfg(target_os = "macos")]
+pub use macos::{
+    capture_screen, list_target_windows, listen_clicks, listen_keys, listen_pen, listen_wheel,
+    register_hotkey, track_target_window,
+};
+#[cfg(target_os = "windows")]
+pub use windows::{
+    capture_screen, list_target_windows, listen_clicks, listen_keys, listen_pen, listen_wheel,
+    register_hotkey, track_target_window,
+};
+
 pub trait WindowExt {
     fn setup_main_window(&self) {}
 
@@ -6,14 +25,628 @@ pub trait WindowExt {
     fn set_hidden(&self, hidden: bool);
 
     fn set_ignore_cursor_events(&self, ignore: bool);
+
+    /// Move the window so that its top-left corner is at `position` in the desktop coordinates.
+    fn set_position(&self, position: dpi::LogicalPosition<i32>);
+}
+
+/// The application window which a canvas can be pinned to.
+#[derive(Clone, Debug)]
+pub struct TargetWindow {
+    pub id: u64,
+    pub title: String,
+    /// The position of the window on the desktop,
+    /// which is in physical pixels on Windows and in points on macOS.
+    pub origin: dpi::Position,
+    pub size: dpi::Size,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TargetWindowEvent {
+    Moved {
+        origin: dpi::Position,
+        size: dpi::Size,
+    },
+    Minimized,
+    Restored,
+    Closed,
+}
+
+/// The interval to check the state of the target window.
+const TRACKING_INTERVAL: Duration = Duration::from_millis(30);
+
+/// Keep tracking something such as the target window on a background thread until this is dropped.
+pub struct Tracker {
+    stopped: Arc<AtomicBool>,
+    /// Undo what was set up on the main thread, e.g. a hook, which can not be undone by the thread.
+    release: Option<Box<dyn FnOnce()>>,
+}
+
+impl Tracker {
+    fn spawn(f: impl FnOnce(&AtomicBool) + Send + 'static) -> Self {
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        std::thread::spawn({
+            let stopped = stopped.clone();
+            move || f(&stopped)
+        });
+
+        Self {
+            stopped,
+            release: None,
+        }
+    }
+
+    /// Keep what was set up on the current thread until this is dropped, which calls `release`.
+    fn on_current_thread(release: impl FnOnce() + 'static) -> Self {
+        Self {
+            stopped: Arc::new(AtomicBool::new(false)),
+            release: Some(Box::new(release)),
+        }
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+
+        if let Some(release) = self.release.take() {
+            release();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClickButton {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MouseClick {
+    pub button: ClickButton,
+    /// The position of the cursor on the desktop.
+    pub position: dpi::Position,
+}
+
+/// The mouse wheel turned with Alt held down, see `listen_wheel`.
+///
+/// The wheel without Alt still scrolls the applications under the canvas.
+#[derive(Clone, Copy, Debug)]
+pub struct WheelEvent {
+    /// The position of the cursor on the desktop.
+    pub position: dpi::Position,
+    /// The number of the notches turned, positive when it is turned up.
+    pub notches: f32,
+    pub shift: bool,
+    pub control: bool,
+}
+
+/// The interval to check the state of the mouse buttons and the keys.
+/// It is shorter than the time of a usual click or key press.
+const INPUT_POLLING_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Watch the mouse buttons on a background thread and call `callback` when they are pressed.
+/// `poll` returns whether the left and right buttons are pressed and the position of the cursor.
+///
+/// We poll the state because hooking the input of other applications needs
+/// extra privileges on some platforms.
+fn spawn_click_listener(
+    mut poll: impl FnMut() -> Option<([bool; 2], dpi::Position)> + Send + 'static,
+    callback: impl Fn(MouseClick) + Send + 'static,
+) -> Tracker {
+    const BUTTONS: [ClickButton; 2] = [ClickButton::Left, ClickButton::Right];
+
+    Tracker::spawn(move |stopped| {
+        let mut was_pressed = [false; 2];
+
+        while !stopped.load(Ordering::Relaxed) {
+            if let Some((pressed, position)) = poll() {
+                for (i, button) in BUTTONS.into_iter().enumerate() {
+                    if pressed[i] && !was_pressed[i] {
+                        callback(MouseClick { button, position });
+                    }
+                }
+
+                was_pressed = pressed;
+            }
+
+            std::thread::sleep(INPUT_POLLING_INTERVAL);
+        }
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Modifier {
+    Control,
+    Alt,
+    Shift,
+    Super,
+}
+
+impl std::fmt::Display for Modifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Control => "Ctrl",
+            Self::Alt if cfg!(target_os = "macos") => "Option",
+            Self::Alt => "Alt",
+            Self::Shift => "Shift",
+            Self::Super if cfg!(target_os = "macos") => "Cmd",
+            Self::Super if cfg!(target_os = "windows") => "Win",
+            Self::Super => "Super",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Key {
+    Modifier(Modifier),
+    /// The other key with its label such as `A` or `Enter`.
+    Other(String),
+}
+
+/// The key pressed with the modifiers held down, e.g. `Ctrl + Shift + P`.
+#[derive(Clone, Debug)]
+pub struct KeyStroke {
+    pub modifiers: Vec<Modifier>,
+    pub key: String,
+}
+
+impl std::fmt::Display for KeyStroke {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{modifier} + ")?;
+        }
+
+        f.write_str(&self.key)
+    }
+}
+
+/// Watch the keyboard on a background thread and call `callback` when a key other than
+/// the modifiers is pressed. `poll` returns the keys which are pressed.
+fn spawn_key_listener(
+    mut poll: impl FnMut() -> Option<Vec<Key>> + Send + 'static,
+    callback: impl Fn(KeyStroke) + Send + 'static,
+) -> Tracker {
+    Tracker::spawn(move |stopped| {
+        let mut was_pressed = Vec::new();
+
+        while !stopped.load(Ordering::Relaxed) {
+            if let Some(pressed) = poll() {
+                let mut modifiers: Vec<_> = pressed
+                    .iter()
+                    .filter_map(|key| match key {
+                        Key::Modifier(modifier) => Some(*modifier),
+                        Key::Other(_) => None,
+                    })
+                    .collect();
+                modifiers.sort();
+                modifiers.dedup();
+
+                for key in &pressed {
+                    if let Key::Other(name) = key
+                        && !was_pressed.contains(key)
+                    {
+                        callback(KeyStroke {
+                            modifiers: modifiers.clone(),
+                            key: name.clone(),
+                        });
+                    }
+                }
+
+                was_pressed = pressed;
+            }
+
+            std::thread::sleep(INPUT_POLLING_INTERVAL);
+        }
+    })
+}
+
+/// The image captured from the screen, see `capture_screen`.
+pub struct CapturedImage {
+    /// The size in pixels, which is the captured size multiplied by the scale factor.
+    pub width: u32,
+    pub height: u32,
+    /// The pixels in BGRA order.
+    pub data: Vec<u8>,
+}
+
+/// The state of the stylus of a tablet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PenState {
+    /// From 0 to 1.
+    pub pressure: f32,
+    /// The tilt along the x and y axes from -1 to 1, if the tablet reports it.
+    pub tilt: Option<(f32, f32)>,
+    /// Whether the eraser end of the stylus is used.
+    pub eraser: bool,
 }
 
 #[cfg(target_os = "macos")]
 pub mod macos {
-    use objc2::rc::Retained;
-    use objc2_app_kit::{NSColor, NSView, NSWindow, NSWindowCollectionBehavior, NSWindowLevel};
+    use std::{
+        ffi::c_void,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use objc2::{MainThreadMarker, rc::Retained};
+    use objc2_app_kit::{
+        NSColor, NSEvent, NSScreen, NSView, NSWindow, NSWindowCollectionBehavior, NSWindowLevel,
+    };
+    use objc2_foundation::NSPoint;
     use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 
+    use anyhow::Context as _;
+
+    use super::{Key, KeyStroke, Modifier, PenState, TargetWindow, TargetWindowEvent, Tracker};
+
+    /// The functions of Core Graphics, Core Foundation and Carbon which are not wrapped by `objc2`.
+    #[allow(non_upper_case_globals, non_snake_case)]
+    mod sys {
+        use std::ffi::c_void;
+
+        pub type CGEventRef = *mut c_void;
+        pub type CFMachPortRef = *mut c_void;
+        pub type CFRunLoopSourceRef = *mut c_void;
+        pub type CFStringRef = *const c_void;
+        pub type CFArrayRef = *const c_void;
+        pub type CFDictionaryRef = *const c_void;
+        pub type CFNumberRef = *const c_void;
+        pub type CFBooleanRef = *const c_void;
+        pub type CGImageRef = *mut c_void;
+        pub type CGColorSpaceRef = *mut c_void;
+        pub type CGContextRef = *mut c_void;
+
+        pub type CGEventTapCallBack = unsafe extern "C" fn(
+            proxy: *mut c_void,
+            event_type: u32,
+            event: CGEventRef,
+            user_info: *mut c_void,
+        ) -> CGEventRef;
+
+        #[repr(C)]
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        pub struct CGPoint {
+            pub x: f64,
+            pub y: f64,
+        }
+
+        #[repr(C)]
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        pub struct CGSize {
+            pub width: f64,
+            pub height: f64,
+        }
+
+        #[repr(C)]
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        pub struct CGRect {
+            pub origin: CGPoint,
+            pub size: CGSize,
+        }
+
+        pub const kCGSessionEventTap: u32 = 1;
+        pub const kCGHeadInsertEventTap: u32 = 0;
+        pub const kCGEventTapOptionDefault: u32 = 0;
+        pub const kCGEventTapOptionListenOnly: u32 = 1;
+
+        pub const kCGEventLeftMouseDown: u32 = 1;
+        pub const kCGEventLeftMouseUp: u32 = 2;
+        pub const kCGEventMouseMoved: u32 = 5;
+        pub const kCGEventLeftMouseDragged: u32 = 6;
+        pub const kCGEventScrollWheel: u32 = 22;
+        pub const kCGEventTabletPointer: u32 = 23;
+        pub const kCGEventTabletProximity: u32 = 24;
+        pub const kCGEventTapDisabledByTimeout: u32 = 0xFFFFFFFE;
+        pub const kCGEventTapDisabledByUserInput: u32 = 0xFFFFFFFF;
+
+        pub const kCGEventSourceStateHIDSystemState: i32 = 1;
+
+        pub const kCGMouseEventPressure: u32 = 2;
+        pub const kCGMouseEventSubtype: u32 = 7;
+        pub const kCGScrollWheelEventDeltaAxis1: u32 = 11;
+        pub const kCGScrollWheelEventDeltaAxis2: u32 = 12;
+        pub const kCGTabletEventPointPressure: u32 = 19;
+        pub const kCGTabletEventTiltX: u32 = 20;
+        pub const kCGTabletEventTiltY: u32 = 21;
+        pub const kCGTabletProximityEventPointerType: u32 = 37;
+        pub const kCGTabletProximityEventEnterProximity: u32 = 38;
+
+        pub const kCGEventFlagMaskShift: u64 = 0x00020000;
+        pub const kCGEventFlagMaskControl: u64 = 0x00040000;
+        pub const kCGEventFlagMaskAlternate: u64 = 0x00080000;
+
+        pub const kCGWindowListOptionOnScreenOnly: u32 = 1 << 0;
+        pub const kCGWindowListOptionIncludingWindow: u32 = 1 << 3;
+        pub const kCGWindowListExcludeDesktopElements: u32 = 1 << 4;
+        pub const kCGNullWindowID: u32 = 0;
+        pub const kCGWindowImageBestResolution: u32 = 1 << 3;
+
+        pub const kCGImageAlphaPremultipliedFirst: u32 = 2;
+        pub const kCGBitmapByteOrder32Little: u32 = 2 << 12;
+
+        pub const kCGEventMouseSubtypeTabletPoint: i64 = 1;
+        /// `NSPointingDeviceType.eraser`.
+        pub const NSPointingDeviceTypeEraser: i64 = 3;
+
+        #[link(name = "CoreGraphics", kind = "framework")]
+        unsafe extern "C" {
+            pub fn CGEventTapCreate(
+                tap: u32,
+                place: u32,
+                options: u32,
+                events_of_interest: u64,
+                callback: CGEventTapCallBack,
+                user_info: *mut c_void,
+            ) -> CFMachPortRef;
+            pub fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
+            pub fn CGEventGetIntegerValueField(event: CGEventRef, field: u32) -> i64;
+            pub fn CGEventGetDoubleValueField(event: CGEventRef, field: u32) -> f64;
+            pub fn CGEventGetFlags(event: CGEventRef) -> u64;
+            pub fn CGEventGetLocation(event: CGEventRef) -> CGPoint;
+            pub fn CGEventSourceKeyState(state_id: i32, key: u16) -> bool;
+            pub fn CGPreflightListenEventAccess() -> bool;
+            pub fn CGRequestListenEventAccess() -> bool;
+
+            pub static kCGWindowNumber: CFStringRef;
+            pub static kCGWindowOwnerPID: CFStringRef;
+            pub static kCGWindowOwnerName: CFStringRef;
+            pub static kCGWindowName: CFStringRef;
+            pub static kCGWindowLayer: CFStringRef;
+            pub static kCGWindowBounds: CFStringRef;
+            pub static kCGWindowIsOnscreen: CFStringRef;
+
+            pub fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: u32) -> CFArrayRef;
+            pub fn CGRectMakeWithDictionaryRepresentation(
+                dict: CFDictionaryRef,
+                rect: *mut CGRect,
+            ) -> bool;
+
+            pub fn CGPreflightScreenCaptureAccess() -> bool;
+            pub fn CGRequestScreenCaptureAccess() -> bool;
+            pub fn CGWindowListCreateImage(
+                bounds: CGRect,
+                list_option: u32,
+                window_id: u32,
+                image_option: u32,
+            ) -> CGImageRef;
+            pub fn CGImageGetWidth(image: CGImageRef) -> usize;
+            pub fn CGImageGetHeight(image: CGImageRef) -> usize;
+            pub fn CGImageRelease(image: CGImageRef);
+            pub fn CGColorSpaceCreateDeviceRGB() -> CGColorSpaceRef;
+            pub fn CGColorSpaceRelease(color_space: CGColorSpaceRef);
+            pub fn CGBitmapContextCreate(
+                data: *mut c_void,
+                width: usize,
+                height: usize,
+                bits_per_component: usize,
+                bytes_per_row: usize,
+                color_space: CGColorSpaceRef,
+                bitmap_info: u32,
+            ) -> CGContextRef;
+            pub fn CGContextDrawImage(context: CGContextRef, rect: CGRect, image: CGImageRef);
+            pub fn CGContextRelease(context: CGContextRef);
+        }
+
+        pub type EventHandlerCallRef = *mut c_void;
+        pub type EventRef = *mut c_void;
+        pub type EventTargetRef = *mut c_void;
+        pub type EventHandlerRef = *mut c_void;
+        pub type EventHotKeyRef = *mut c_void;
+
+        pub type EventHandlerProcPtr = unsafe extern "C" fn(
+            call_ref: EventHandlerCallRef,
+            event: EventRef,
+            user_data: *mut c_void,
+        ) -> i32;
+
+        #[repr(C)]
+        pub struct EventTypeSpec {
+            pub eventClass: u32,
+            pub eventKind: u32,
+        }
+
+        #[repr(C)]
+        #[derive(Clone, Copy, Default)]
+        pub struct EventHotKeyID {
+            pub signature: u32,
+            pub id: u32,
+        }
+
+        pub const noErr: i32 = 0;
+        pub const eventNotHandledErr: i32 = -9874;
+        /// `'keyb'`
+        pub const kEventClassKeyboard: u32 = u32::from_be_bytes(*b"keyb");
+        pub const kEventHotKeyPressed: u32 = 5;
+        /// `'----'`
+        pub const kEventParamDirectObject: u32 = u32::from_be_bytes(*b"----");
+        /// `'hkid'`
+        pub const typeEventHotKeyID: u32 = u32::from_be_bytes(*b"hkid");
+
+        pub const cmdKey: u32 = 1 << 8;
+        pub const shiftKey: u32 = 1 << 9;
+        pub const optionKey: u32 = 1 << 11;
+        pub const controlKey: u32 = 1 << 12;
+
+        #[link(name = "Carbon", kind = "framework")]
+        unsafe extern "C" {
+            pub fn GetApplicationEventTarget() -> EventTargetRef;
+            pub fn InstallEventHandler(
+                target: EventTargetRef,
+                handler: EventHandlerProcPtr,
+                num_types: usize,
+                list: *const EventTypeSpec,
+                user_data: *mut c_void,
+                out_ref: *mut EventHandlerRef,
+            ) -> i32;
+            pub fn RemoveEventHandler(handler: EventHandlerRef) -> i32;
+            pub fn GetEventParameter(
+                event: EventRef,
+                name: u32,
+                desired_type: u32,
+                actual_type: *mut u32,
+                buffer_size: usize,
+                actual_size: *mut usize,
+                data: *mut c_void,
+            ) -> i32;
+            pub fn RegisterEventHotKey(
+                key_code: u32,
+                modifiers: u32,
+                id: EventHotKeyID,
+                target: EventTargetRef,
+                options: u32,
+                out_ref: *mut EventHotKeyRef,
+            ) -> i32;
+            pub fn UnregisterEventHotKey(hot_key: EventHotKeyRef) -> i32;
+        }
+
+        #[link(name = "CoreFoundation", kind = "framework")]
+        unsafe extern "C" {
+            pub static kCFRunLoopDefaultMode: CFStringRef;
+
+            pub fn CFMachPortCreateRunLoopSource(
+                allocator: *const c_void,
+                port: CFMachPortRef,
+                order: isize,
+            ) -> CFRunLoopSourceRef;
+            pub fn CFMachPortInvalidate(port: CFMachPortRef);
+            pub fn CFRunLoopGetCurrent() -> *mut c_void;
+            pub fn CFRunLoopAddSource(
+                run_loop: *mut c_void,
+                source: CFRunLoopSourceRef,
+                mode: CFStringRef,
+            );
+            pub fn CFRunLoopRunInMode(
+                mode: CFStringRef,
+                seconds: f64,
+                return_after_source_handled: u8,
+            ) -> i32;
+            pub fn CFRelease(object: *const c_void);
+
+            pub fn CFArrayGetCount(array: CFArrayRef) -> isize;
+            pub fn CFArrayGetValueAtIndex(array: CFArrayRef, index: isize) -> *const c_void;
+            pub fn CFDictionaryGetValue(dict: CFDictionaryRef, key: *const c_void)
+            -> *const c_void;
+            pub fn CFNumberGetValue(
+                number: CFNumberRef,
+                number_type: isize,
+                value: *mut c_void,
+            ) -> bool;
+            pub fn CFBooleanGetValue(boolean: CFBooleanRef) -> bool;
+            pub fn CFStringGetLength(string: CFStringRef) -> isize;
+            pub fn CFStringGetMaximumSizeForEncoding(length: isize, encoding: u32) -> isize;
+            pub fn CFStringGetCString(
+                string: CFStringRef,
+                buffer: *mut std::ffi::c_char,
+                buffer_size: isize,
+                encoding: u32,
+            ) -> bool;
+        }
+
+        pub const kCFNumberSInt64Type: isize = 4;
+        pub const kCFStringEncodingUTF8: u32 = 0x08000100;
+    }
+
+    /// The event tap and the handler of its events, which is passed to the callback.
+    struct EventTap {
+        port: sys::CFMachPortRef,
+        /// Returns whether the event goes on to the applications.
+        handle: Box<dyn FnMut(u32, sys::CGEventRef) -> bool>,
+    }
+
+    unsafe extern "C" fn event_tap_callback(
+        _proxy: *mut c_void,
+        event_type: u32,
+        event: sys::CGEventRef,
+        user_info: *mut c_void,
+    ) -> sys::CGEventRef {
+        let tap = unsafe { &mut *user_info.cast::<EventTap>() };
+
+        // The system turns the tap off if the callback is slow, so turn it on again.
+        if matches!(
+            event_type,
+            sys::kCGEventTapDisabledByTimeout | sys::kCGEventTapDisabledByUserInput
+        ) {
+            unsafe { sys::CGEventTapEnable(tap.port, true) };
+            return event;
+        }
+
+        if (tap.handle)(event_type, event) {
+            event
+        } else {
+            std::ptr::null_mut()
+        }
+    }
+
+    const INPUT_PERMISSION_ERROR: &str = "Monica is not allowed to monitor the input. \
+         Allow it in Privacy & Security of the System Settings";
+
+    /// Watch the events of all applications with an event tap on a background thread.
+    /// `handle` returns whether the event goes on, which is ignored if `options` is listen-only.
+    ///
+    /// The tap needs the permission for the input monitoring, or the accessibility to take events.
+    fn spawn_event_tap(
+        events: &'static [u32],
+        options: u32,
+        handle: impl FnMut(u32, sys::CGEventRef) -> bool + Send + 'static,
+    ) -> anyhow::Result<Tracker> {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let tracker = Tracker::spawn(move |stopped| {
+            let mut tap = Box::new(EventTap {
+                port: std::ptr::null_mut(),
+                handle: Box::new(handle),
+            });
+            let mask = events.iter().fold(0u64, |mask, event| mask | 1 << event);
+
+            unsafe {
+                tap.port = sys::CGEventTapCreate(
+                    sys::kCGSessionEventTap,
+                    sys::kCGHeadInsertEventTap,
+                    options,
+                    mask,
+                    event_tap_callback,
+                    (&raw mut *tap).cast(),
+                );
+                if tap.port.is_null() {
+                    _ = tx.send(false);
+                    return;
+                }
+                _ = tx.send(true);
+
+                let source = sys::CFMachPortCreateRunLoopSource(std::ptr::null(), tap.port, 0);
+                sys::CFRunLoopAddSource(
+                    sys::CFRunLoopGetCurrent(),
+                    source,
+                    sys::kCFRunLoopDefaultMode,
+                );
+                sys::CGEventTapEnable(tap.port, true);
+
+                let timeout = super::INPUT_POLLING_INTERVAL.as_secs_f64();
+                while !stopped.load(Ordering::Relaxed) {
+                    sys::CFRunLoopRunInMode(sys::kCFRunLoopDefaultMode, timeout, 0);
+                }
+
+                sys::CFMachPortInvalidate(tap.port);
+                sys::CFRelease(source.cast_const());
+                sys::CFRelease(tap.port.cast_const());
+            }
+        });
+
+        if !rx.recv()? {
+            anyhow::bail!(INPUT_PERMISSION_ERROR);
+        }
+
+        Ok(tracker)
+    }
+
+    fn primary_screen_height(mtm: MainThreadMarker) -> f64 {
+        NSScreen::screens(mtm)
+            .firstObject()
+            .map(|screen| screen.frame().size.height)
+            .unwrap_or_default()
+    }
+
     fn get_ns_window(window: &gpui::Window) -> Retained<NSWindow> {
         let handle = HasWindowHandle::window_handle(window).unwrap().as_raw();
         let ns_view: Retained<NSView> = match handle {
@@ -64,15 +697,634 @@ pub mod macos {
         fn set_ignore_cursor_events(&self, ignore: bool) {
             get_ns_window(self).setIgnoresMouseEvents(ignore);
         }
+
+        fn set_position(&self, position: dpi::LogicalPosition<i32>) {
+            // AppKit uses the bottom-left corner of the primary screen as the origin,
+            // so we flip the y coordinate.
+            let mtm = MainThreadMarker::new().expect("Not on the main thread.");
+            let primary_height = primary_screen_height(mtm);
+
+            let top_left = NSPoint::new(position.x as _, primary_height - position.y as f64);
+            get_ns_window(self).setFrameTopLeftPoint(top_left);
+        }
+    }
+
+    fn cf_number(value: *const c_void) -> Option<i64> {
+        let mut number = 0i64;
+        (!value.is_null()
+            && unsafe {
+                sys::CFNumberGetValue(value, sys::kCFNumberSInt64Type, (&raw mut number).cast())
+            })
+        .then_some(number)
+    }
+
+    fn cf_string(value: *const c_void) -> Option<String> {
+        if value.is_null() {
+            return None;
+        }
+
+        let length = unsafe { sys::CFStringGetLength(value) };
+        let size =
+            unsafe { sys::CFStringGetMaximumSizeForEncoding(length, sys::kCFStringEncodingUTF8) }
+                + 1;
+        let mut buffer = vec![0u8; size as usize];
+
+        let copied = unsafe {
+            sys::CFStringGetCString(
+                value,
+                buffer.as_mut_ptr().cast(),
+                size,
+                sys::kCFStringEncodingUTF8,
+            )
+        };
+        if !copied {
+            return None;
+        }
+
+        let string = std::ffi::CStr::from_bytes_until_nul(&buffer).ok()?;
+        Some(string.to_string_lossy().into_owned())
+    }
+
+    /// A window in the window list of the window server.
+    struct WindowInfo {
+        id: u32,
+        pid: i64,
+        layer: i64,
+        /// The name of the window, or the name of the application without the permission
+        /// for the screen recording, which is needed to get the names of the windows.
+        title: String,
+        /// The frame in points from the top-left corner of the primary screen.
+        bounds: sys::CGRect,
+        /// Whether the window is shown, which is not the case when it is minimized or hidden.
+        on_screen: bool,
+    }
+
+    impl WindowInfo {
+        fn rect(&self) -> (dpi::Position, dpi::Size) {
+            let sys::CGRect { origin, size } = self.bounds;
+
+            (
+                dpi::LogicalPosition::new(origin.x, origin.y).into(),
+                dpi::LogicalSize::new(size.width, size.height).into(),
+            )
+        }
+    }
+
+    /// Get the windows in the window list from the frontmost one,
+    /// see `CGWindowListCopyWindowInfo` for `option` and `window_id`.
+    fn window_list(option: u32, window_id: u32) -> Vec<WindowInfo> {
+        let list = unsafe { sys::CGWindowListCopyWindowInfo(option, window_id) };
+        if list.is_null() {
+            return Vec::new();
+        }
+
+        let windows = (0..unsafe { sys::CFArrayGetCount(list) })
+            .filter_map(|index| {
+                let info = unsafe { sys::CFArrayGetValueAtIndex(list, index) };
+                let get = |key| unsafe { sys::CFDictionaryGetValue(info, key) };
+
+                let mut bounds = sys::CGRect::default();
+                if !unsafe {
+                    sys::CGRectMakeWithDictionaryRepresentation(
+                        get(sys::kCGWindowBounds),
+                        &raw mut bounds,
+                    )
+                } {
+                    return None;
+                }
+
+                let on_screen = get(unsafe { sys::kCGWindowIsOnscreen });
+
+                Some(WindowInfo {
+                    id: cf_number(get(unsafe { sys::kCGWindowNumber }))? as _,
+                    pid: cf_number(get(unsafe { sys::kCGWindowOwnerPID }))?,
+                    layer: cf_number(get(unsafe { sys::kCGWindowLayer }))?,
+                    title: cf_string(get(unsafe { sys::kCGWindowName }))
+                        .filter(|name| !name.is_empty())
+                        .or_else(|| cf_string(get(unsafe { sys::kCGWindowOwnerName })))
+                        .unwrap_or_default(),
+                    bounds,
+                    on_screen: !on_screen.is_null() && unsafe { sys::CFBooleanGetValue(on_screen) },
+                })
+            })
+            .collect();
+
+        unsafe { sys::CFRelease(list) };
+        windows
+    }
+
+    pub fn list_target_windows() -> Vec<TargetWindow> {
+        let options =
+            sys::kCGWindowListOptionOnScreenOnly | sys::kCGWindowListExcludeDesktopElements;
+
+        window_list(options, 0)
+            .into_iter()
+            // Skip untitled windows, our windows and the ones above the normal windows
+            // such as the menu bar.
+            .filter(|window| {
+                !window.title.is_empty()
+                    && window.pid != std::process::id() as i64
+                    && window.layer == 0
+            })
+            .map(|window| {
+                let (origin, size) = window.rect();
+
+                TargetWindow {
+                    id: window.id as _,
+                    title: window.title,
+                    origin,
+                    size,
+                }
+            })
+            .collect()
+    }
+
+    pub fn track_target_window(
+        id: u64,
+        callback: impl Fn(TargetWindowEvent) + Send + 'static,
+    ) -> anyhow::Result<Tracker> {
+        let find_window = move || {
+            window_list(sys::kCGWindowListOptionIncludingWindow, id as _)
+                .into_iter()
+                .find(|window| window.id as u64 == id)
+        };
+
+        if find_window().is_none() {
+            anyhow::bail!("The window is already closed");
+        }
+
+        // The geometry of other application windows is not notified without
+        // the accessibility API, so we poll the window list.
+        Ok(Tracker::spawn(move |stopped| {
+            let mut last_bounds = None;
+            let mut minimized = false;
+
+            while !stopped.load(Ordering::Relaxed) {
+                let Some(window) = find_window() else {
+                    callback(TargetWindowEvent::Closed);
+                    break;
+                };
+
+                if window.on_screen == minimized {
+                    minimized = !window.on_screen;
+                    callback(if minimized {
+                        TargetWindowEvent::Minimized
+                    } else {
+                        TargetWindowEvent::Restored
+                    });
+                }
+
+                if !minimized && last_bounds != Some(window.bounds) {
+                    last_bounds = Some(window.bounds);
+
+                    let (origin, size) = window.rect();
+                    callback(TargetWindowEvent::Moved { origin, size });
+                }
+
+                std::thread::sleep(super::TRACKING_INTERVAL);
+            }
+        }))
+    }
+
+    pub fn listen_clicks(
+        callback: impl Fn(super::MouseClick) + Send + 'static,
+    ) -> anyhow::Result<super::Tracker> {
+        let mtm = MainThreadMarker::new().expect("Not on the main thread.");
+        let primary_height = primary_screen_height(mtm);
+
+        Ok(super::spawn_click_listener(
+            move || {
+                let buttons = NSEvent::pressedMouseButtons();
+                let location = NSEvent::mouseLocation();
+                let position = dpi::LogicalPosition::new(location.x, primary_height - location.y);
+
+                Some(([buttons & 1 != 0, buttons & 2 != 0], position.into()))
+            },
+            callback,
+        ))
+    }
+
+    /// Take the wheel turned with Option from the application under the cursor.
+    /// Taking the events needs the permission for the accessibility.
+    pub fn listen_wheel(
+        callback: impl Fn(super::WheelEvent) + Send + 'static,
+    ) -> anyhow::Result<Tracker> {
+        spawn_event_tap(
+            &[sys::kCGEventScrollWheel],
+            sys::kCGEventTapOptionDefault,
+            move |_, event| {
+                let flags = unsafe { sys::CGEventGetFlags(event) };
+                if flags & sys::kCGEventFlagMaskAlternate == 0 {
+                    return true;
+                }
+
+                // Shift turns the wheel into the horizontal scroll.
+                let delta = |axis| unsafe { sys::CGEventGetIntegerValueField(event, axis) };
+                let notches = match delta(sys::kCGScrollWheelEventDeltaAxis1) {
+                    0 => delta(sys::kCGScrollWheelEventDeltaAxis2),
+                    notches => notches,
+                };
+
+                if notches != 0 {
+                    // The location is in points from the top-left corner of the primary screen.
+                    let location = unsafe { sys::CGEventGetLocation(event) };
+
+                    callback(super::WheelEvent {
+                        position: dpi::LogicalPosition::new(location.x, location.y).into(),
+                        notches: notches as f32,
+                        shift: flags & sys::kCGEventFlagMaskShift != 0,
+                        control: flags & sys::kCGEventFlagMaskControl != 0,
+                    });
+                }
+
+                false
+            },
+        )
+    }
+
+    /// The hotkey registered with Carbon, which is passed to `hotkey_handler`.
+    struct HotKey {
+        id: u32,
+        callback: Box<dyn Fn()>,
+    }
+
+    unsafe extern "C" fn hotkey_handler(
+        _call_ref: sys::EventHandlerCallRef,
+        event: sys::EventRef,
+        user_data: *mut c_void,
+    ) -> i32 {
+        let hotkey = unsafe { &*user_data.cast::<HotKey>() };
+
+        let mut id = sys::EventHotKeyID::default();
+        let status = unsafe {
+            sys::GetEventParameter(
+                event,
+                sys::kEventParamDirectObject,
+                sys::typeEventHotKeyID,
+                std::ptr::null_mut(),
+                size_of::<sys::EventHotKeyID>(),
+                std::ptr::null_mut(),
+                (&raw mut id).cast(),
+            )
+        };
+
+        // The handler receives the hotkeys registered by the others too.
+        if status != sys::noErr || id.id != hotkey.id {
+            return sys::eventNotHandledErr;
+        }
+
+        (hotkey.callback)();
+        sys::noErr
+    }
+
+    /// Register the hotkey on the main thread, whose event loop receives it.
+    pub fn register_hotkey(
+        hotkey: &KeyStroke,
+        callback: impl Fn() + Send + 'static,
+    ) -> anyhow::Result<Tracker> {
+        static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+        const SIGNATURE: u32 = u32::from_be_bytes(*b"MNCA");
+        const EVENT_TYPE: sys::EventTypeSpec = sys::EventTypeSpec {
+            eventClass: sys::kEventClassKeyboard,
+            eventKind: sys::kEventHotKeyPressed,
+        };
+
+        let key_code = (0..0x80)
+            .find(|&code| {
+                matches!(key_from_key_code(code), Some(Key::Other(name)) if name.eq_ignore_ascii_case(&hotkey.key))
+            })
+            .with_context(|| format!("`{}` is not a key", hotkey.key))?;
+        let modifiers = hotkey.modifiers.iter().fold(0, |modifiers, modifier| {
+            modifiers
+                | match modifier {
+                    Modifier::Control => sys::controlKey,
+                    Modifier::Alt => sys::optionKey,
+                    Modifier::Shift => sys::shiftKey,
+                    Modifier::Super => sys::cmdKey,
+                }
+        });
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let user_data = Box::into_raw(Box::new(HotKey {
+            id,
+            callback: Box::new(callback),
+        }));
+
+        unsafe {
+            let target = sys::GetApplicationEventTarget();
+
+            let mut handler = std::ptr::null_mut();
+            let status = sys::InstallEventHandler(
+                target,
+                hotkey_handler,
+                1,
+                &EVENT_TYPE,
+                user_data.cast(),
+                &mut handler,
+            );
+            if status != sys::noErr {
+                drop(Box::from_raw(user_data));
+                anyhow::bail!("Failed to handle the shortcut keys ({status})");
+            }
+
+            let mut hotkey_ref = std::ptr::null_mut();
+            let status = sys::RegisterEventHotKey(
+                key_code.into(),
+                modifiers,
+                sys::EventHotKeyID {
+                    signature: SIGNATURE,
+                    id,
+                },
+                target,
+                0,
+                &mut hotkey_ref,
+            );
+            if status != sys::noErr {
+                sys::RemoveEventHandler(handler);
+                drop(Box::from_raw(user_data));
+                anyhow::bail!("Another application has taken `{hotkey}`");
+            }
+
+            Ok(Tracker::on_current_thread(move || {
+                sys::UnregisterEventHotKey(hotkey_ref);
+                sys::RemoveEventHandler(handler);
+                drop(Box::from_raw(user_data));
+            }))
+        }
+    }
+
+    /// Get the key of the virtual key code, named in the same way as on Windows.
+    fn key_from_key_code(code: u16) -> Option<Key> {
+        let name = match code {
+            // The left and right modifiers.
+            0x38 | 0x3C => return Some(Key::Modifier(Modifier::Shift)),
+            0x3B | 0x3E => return Some(Key::Modifier(Modifier::Control)),
+            0x3A | 0x3D => return Some(Key::Modifier(Modifier::Alt)),
+            0x37 | 0x36 => return Some(Key::Modifier(Modifier::Super)),
+            0x00 => "A",
+            0x0B => "B",
+            0x08 => "C",
+            0x02 => "D",
+            0x0E => "E",
+            0x03 => "F",
+            0x05 => "G",
+            0x04 => "H",
+            0x22 => "I",
+            0x26 => "J",
+            0x28 => "K",
+            0x25 => "L",
+            0x2E => "M",
+            0x2D => "N",
+            0x1F => "O",
+            0x23 => "P",
+            0x0C => "Q",
+            0x0F => "R",
+            0x01 => "S",
+            0x11 => "T",
+            0x20 => "U",
+            0x09 => "V",
+            0x0D => "W",
+            0x07 => "X",
+            0x10 => "Y",
+            0x06 => "Z",
+            0x1D => "0",
+            0x12 => "1",
+            0x13 => "2",
+            0x14 => "3",
+            0x15 => "4",
+            0x17 => "5",
+            0x16 => "6",
+            0x1A => "7",
+            0x1C => "8",
+            0x19 => "9",
+            0x7A => "F1",
+            0x78 => "F2",
+            0x63 => "F3",
+            0x76 => "F4",
+            0x60 => "F5",
+            0x61 => "F6",
+            0x62 => "F7",
+            0x64 => "F8",
+            0x65 => "F9",
+            0x6D => "F10",
+            0x67 => "F11",
+            0x6F => "F12",
+            0x33 => "Backspace",
+            0x30 => "Tab",
+            0x24 => "Enter",
+            0x35 => "Esc",
+            0x31 => "Space",
+            0x74 => "PgUp",
+            0x79 => "PgDn",
+            0x77 => "End",
+            0x73 => "Home",
+            0x7B => "←",
+            0x7E => "↑",
+            0x7C => "→",
+            0x7D => "↓",
+            // The Help key is where Insert is on the PC keyboards.
+            0x72 => "Insert",
+            0x75 => "Delete",
+            _ => return None,
+        };
+
+        Some(Key::Other(name.to_owned()))
+    }
+
+    /// Reading the keys of the other applications needs the permission for the input monitoring.
+    pub fn listen_keys(callback: impl Fn(KeyStroke) + Send + 'static) -> anyhow::Result<Tracker> {
+        if !unsafe { sys::CGPreflightListenEventAccess() } {
+            // Ask the user, which takes effect after Monica is launched again.
+            unsafe { sys::CGRequestListenEventAccess() };
+            anyhow::bail!(INPUT_PERMISSION_ERROR);
+        }
+
+        Ok(super::spawn_key_listener(
+            || {
+                let pressed = (0..0x80)
+                    .filter(|&code| unsafe {
+                        sys::CGEventSourceKeyState(sys::kCGEventSourceStateHIDSystemState, code)
+                    })
+                    .filter_map(key_from_key_code)
+                    .collect();
+
+                Some(pressed)
+            },
+            callback,
+        ))
+    }
+
+    /// Capture the area of the desktop in points. The image is at the resolution of the screens,
+    /// so the scale factor is not used.
+    /// Capturing the other applications needs the permission for the screen recording.
+    pub fn capture_screen(
+        origin: dpi::LogicalPosition<f64>,
+        size: dpi::LogicalSize<f64>,
+        _scale_factor: f64,
+    ) -> anyhow::Result<super::CapturedImage> {
+        if !unsafe { sys::CGPreflightScreenCaptureAccess() } {
+            // Ask the user, which takes effect after Monica is launched again.
+            unsafe { sys::CGRequestScreenCaptureAccess() };
+            anyhow::bail!(
+                "Monica is not allowed to record the screen. \
+                 Allow it in Privacy & Security of the System Settings"
+            );
+        }
+
+        let bounds = sys::CGRect {
+            origin: sys::CGPoint {
+                x: origin.x,
+                y: origin.y,
+            },
+            size: sys::CGSize {
+                width: size.width,
+                height: size.height,
+            },
+        };
+        let image = unsafe {
+            sys::CGWindowListCreateImage(
+                bounds,
+                sys::kCGWindowListOptionOnScreenOnly,
+                sys::kCGNullWindowID,
+                sys::kCGWindowImageBestResolution,
+            )
+        };
+        if image.is_null() {
+            anyhow::bail!("Failed to capture the screen");
+        }
+
+        let (width, height) =
+            unsafe { (sys::CGImageGetWidth(image), sys::CGImageGetHeight(image)) };
+        let mut data = vec![0u8; width * height * 4];
+
+        // Draw the image into our buffer, where the little-endian ARGB is BGRA in memory.
+        let drawn = unsafe {
+            let color_space = sys::CGColorSpaceCreateDeviceRGB();
+            let context = sys::CGBitmapContextCreate(
+                data.as_mut_ptr().cast(),
+                width,
+                height,
+                8,
+                width * 4,
+                color_space,
+                sys::kCGImageAlphaPremultipliedFirst | sys::kCGBitmapByteOrder32Little,
+            );
+
+            if !context.is_null() {
+                let rect = sys::CGRect {
+                    size: sys::CGSize {
+                        width: width as _,
+                        height: height as _,
+                    },
+                    ..Default::default()
+                };
+                sys::CGContextDrawImage(context, rect, image);
+                sys::CGContextRelease(context);
+            }
+
+            sys::CGColorSpaceRelease(color_space);
+            sys::CGImageRelease(image);
+            !context.is_null()
+        };
+        if !drawn {
+            anyhow::bail!("Failed to get the pixels of the screen");
+        }
+
+        Ok(super::CapturedImage {
+            width: width as _,
+            height: height as _,
+            data,
+        })
+    }
+
+    pub fn listen_pen(callback: impl Fn(PenState) + Send + 'static) -> anyhow::Result<Tracker> {
+        const EVENTS: [u32; 6] = [
+            sys::kCGEventLeftMouseDown,
+            sys::kCGEventLeftMouseUp,
+            sys::kCGEventMouseMoved,
+            sys::kCGEventLeftMouseDragged,
+            sys::kCGEventTabletPointer,
+            sys::kCGEventTabletProximity,
+        ];
+
+        let mut eraser = false;
+        spawn_event_tap(
+            &EVENTS,
+            sys::kCGEventTapOptionListenOnly,
+            move |event_type, event| {
+                let field = |field| unsafe { sys::CGEventGetDoubleValueField(event, field) };
+                let integer_field =
+                    |field| unsafe { sys::CGEventGetIntegerValueField(event, field) };
+
+                let pressure = if event_type == sys::kCGEventTabletProximity {
+                    // The stylus is turned over when the eraser end comes near the tablet.
+                    eraser = integer_field(sys::kCGTabletProximityEventEnterProximity) != 0
+                        && integer_field(sys::kCGTabletProximityEventPointerType)
+                            == sys::NSPointingDeviceTypeEraser;
+                    0.
+                } else if event_type == sys::kCGEventTabletPointer {
+                    field(sys::kCGTabletEventPointPressure)
+                } else if integer_field(sys::kCGMouseEventSubtype)
+                    == sys::kCGEventMouseSubtypeTabletPoint
+                {
+                    // The mouse events moved by the stylus carry the state of the stylus.
+                    field(sys::kCGMouseEventPressure)
+                } else {
+                    return true;
+                };
+
+                callback(PenState {
+                    pressure: pressure as f32,
+                    tilt: Some((
+                        field(sys::kCGTabletEventTiltX) as f32,
+                        field(sys::kCGTabletEventTiltY) as f32,
+                    )),
+                    eraser,
+                });
+
+                true
+            },
+        )
     }
 }
 
 #[cfg(target_os = "windows")]
 pub mod windows {
+    use std::{
+        cell::RefCell,
+        sync::atomic::{AtomicBool, Ordering},
+    };
+
+    use anyhow::Context as _;
     use raw_window_handle::{HasWindowHandle, RawWindowHandle};
-    use windows::Win32::{
-        Foundation::{HWND, RECT},
-        UI::WindowsAndMessaging::*,
+    use windows::{
+        Win32::{
+            Foundation::{HWND, LPARAM, LRESULT, POINT, RECT, WPARAM},
+            Graphics::Gdi::{
+                BI_RGB, BITMAPINFO, BITMAPINFOHEADER, BitBlt, CAPTUREBLT, CreateCompatibleBitmap,
+                CreateCompatibleDC, DIB_RGB_COLORS, DeleteDC, DeleteObject, GetDC, GetDIBits,
+                ReleaseDC, SRCCOPY, SelectObject,
+            },
+            System::Threading::GetCurrentThreadId,
+            UI::{
+                Input::{
+                    KeyboardAndMouse::{
+                        GetAsyncKeyState, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBD_EVENT_FLAGS,
+                        KEYBDINPUT, KEYEVENTF_KEYUP, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT,
+                        MOD_WIN, RegisterHotKey, SendInput, UnregisterHotKey, VIRTUAL_KEY,
+                        VK_CONTROL, VK_LBUTTON, VK_MENU, VK_RBUTTON, VK_SHIFT,
+                    },
+                    Pointer::{GetPointerPenInfo, GetPointerType, POINTER_PEN_INFO},
+                },
+                WindowsAndMessaging::*,
+            },
+        },
+        core::BOOL,
+    };
+
+    use super::{
+        CapturedImage, Key, KeyStroke, Modifier, MouseClick, PenState, TargetWindow,
+        TargetWindowEvent, Tracker, WheelEvent,
     };
 
     fn get_hwnd(window: &gpui::Window) -> HWND {
@@ -99,6 +1351,34 @@ pub mod windows {
         };
     }
 
+    /// Get the size of the invisible resize border around the client area.
+    fn get_border_size(hwnd: HWND) -> (i32, i32) {
+        let mut window_rect = RECT::default();
+        let mut client_rect = RECT::default();
+
+        unsafe {
+            _ = GetWindowRect(hwnd, &raw mut window_rect);
+            _ = GetClientRect(hwnd, &raw mut client_rect);
+        }
+
+        let diff_x =
+            (window_rect.right - window_rect.left) - (client_rect.right - client_rect.left);
+        let diff_y =
+            (window_rect.bottom - window_rect.top) - (client_rect.bottom - client_rect.top);
+
+        (diff_x, diff_y)
+    }
+
+    fn get_window_rect(hwnd: HWND) -> Option<(dpi::PhysicalPosition<i32>, dpi::PhysicalSize<u32>)> {
+        let mut rect = RECT::default();
+        unsafe { GetWindowRect(hwnd, &raw mut rect) }.ok()?;
+
+        Some((
+            dpi::PhysicalPosition::new(rect.left, rect.top),
+            dpi::PhysicalSize::new((rect.right - rect.left) as _, (rect.bottom - rect.top) as _),
+        ))
+    }
+
     fn set_always_on_top(hwnd: HWND) {
         unsafe {
             _ = SetWindowPos(
@@ -149,6 +1429,23 @@ pub mod windows {
             let hwnd = get_hwnd(self);
             manage_ex_style(hwnd, ignore, WS_EX_TRANSPARENT | WS_EX_LAYERED);
         }
+
+        fn set_position(&self, position: dpi::LogicalPosition<i32>) {
+            let hwnd = get_hwnd(self);
+            let (diff_x, diff_y) = get_border_size(hwnd);
+
+            unsafe {
+                _ = SetWindowPos(
+                    hwnd,
+                    None,
+                    position.x - diff_x / 2,
+                    position.y - diff_y / 2,
+                    0,
+                    0,
+                    SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+                );
+            }
+        }
     }
 
     pub trait WindowsWindowExt {
@@ -161,21 +1458,12 @@ pub mod windows {
         fn set_window_rect(&self, x: i32, y: i32, width: i32, height: i32) {
             let hwnd = get_hwnd(self);
 
-            unsafe {
-                // Calculate the invisible resize border.
-                let mut window_rect = RECT::default();
-                let mut client_rect = RECT::default();
-
-                _ = GetWindowRect(hwnd, &raw mut window_rect);
-                _ = GetClientRect(hwnd, &raw mut client_rect);
-
-                let diff_x =
-                    (window_rect.right - window_rect.left) - (client_rect.right - client_rect.left);
-                let diff_y =
-                    (window_rect.bottom - window_rect.top) - (client_rect.bottom - client_rect.top);
-                let offset_x = diff_x / 2;
-                let offset_y = diff_y / 2;
+            // Calculate the invisible resize border.
+            let (diff_x, diff_y) = get_border_size(hwnd);
+            let offset_x = diff_x / 2;
+            let offset_y = diff_y / 2;
 
+            unsafe {
                 // Set the window position and size without the invisible resize border.
                 _ = SetWindowPos(
                     hwnd,
@@ -203,4 +1491,419 @@ pub mod windows {
             }
         }
     }
+
+    pub fn list_target_windows() -> Vec<TargetWindow> {
+        unsafe extern "system" fn push_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+            let windows = unsafe { &mut *(lparam.0 as *mut Vec<TargetWindow>) };
+
+            let mut process_id = 0;
+            unsafe { GetWindowThreadProcessId(hwnd, Some(&raw mut process_id)) };
+
+            let mut title = [0u16; 256];
+            let length = unsafe { GetWindowTextW(hwnd, &mut title) };
+
+            // Skip invisible windows, untitled windows and our windows.
+            if length > 0
+                && unsafe { IsWindowVisible(hwnd) }.as_bool()
+                && process_id != std::process::id()
+                && let Some((origin, size)) = get_window_rect(hwnd)
+            {
+                windows.push(TargetWindow {
+                    id: hwnd.0 as _,
+                    title: String::from_utf16_lossy(&title[..length as usize]),
+                    origin: origin.into(),
+                    size: size.into(),
+                });
+            }
+
+            true.into()
+        }
+
+        let mut windows = Vec::new();
+        unsafe {
+            _ = EnumWindows(Some(push_window), LPARAM(&raw mut windows as _));
+        }
+
+        windows
+    }
+
+    pub fn track_target_window(
+        id: u64,
+        callback: impl Fn(TargetWindowEvent) + Send + 'static,
+    ) -> anyhow::Result<Tracker> {
+        if !unsafe { IsWindow(Some(HWND(id as _))) }.as_bool() {
+            anyhow::bail!("The window is already closed");
+        }
+
+        // There is no event for the geometry of other process windows without hooks,
+        // so we poll the state of the window.
+        Ok(Tracker::spawn(move |stopped| {
+            let hwnd = HWND(id as _);
+            let mut last_rect = None;
+            let mut minimized = false;
+
+            while !stopped.load(Ordering::Relaxed) {
+                if !unsafe { IsWindow(Some(hwnd)) }.as_bool() {
+                    callback(TargetWindowEvent::Closed);
+                    break;
+                }
+
+                let iconic = unsafe { IsIconic(hwnd) }.as_bool();
+                if iconic != minimized {
+                    minimized = iconic;
+                    callback(if iconic {
+                        TargetWindowEvent::Minimized
+                    } else {
+                        TargetWindowEvent::Restored
+                    });
+                }
+
+                let rect = get_window_rect(hwnd);
+                if !iconic && rect != last_rect {
+                    last_rect = rect;
+
+                    if let Some((origin, size)) = rect {
+                        callback(TargetWindowEvent::Moved {
+                            origin: origin.into(),
+                            size: size.into(),
+                        });
+                    }
+                }
+
+                std::thread::sleep(super::TRACKING_INTERVAL);
+            }
+        }))
+    }
+
+    pub fn listen_clicks(
+        callback: impl Fn(MouseClick) + Send + 'static,
+    ) -> anyhow::Result<Tracker> {
+        // The most significant bit tells whether the key is down.
+        let is_pressed = |key: VIRTUAL_KEY| unsafe { GetAsyncKeyState(key.0 as _) } < 0;
+
+        Ok(super::spawn_click_listener(
+            move || {
+                let mut point = POINT::default();
+                unsafe { GetCursorPos(&mut point) }.ok()?;
+
+                let pressed = [is_pressed(VK_LBUTTON), is_pressed(VK_RBUTTON)];
+                let position = dpi::PhysicalPosition::new(point.x, point.y);
+
+                Some((pressed, position.into()))
+            },
+            callback,
+        ))
+    }
+
+    thread_local! {
+        /// The callback of the wheel hook, which is called on the thread which installed it.
+        static WHEEL_CALLBACK: RefCell<Option<Box<dyn Fn(WheelEvent)>>> = const { RefCell::new(None) };
+    }
+
+    /// Take the wheel turned with Alt from the application under the cursor.
+    unsafe extern "system" fn wheel_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        let is_pressed = |key: VIRTUAL_KEY| unsafe { GetAsyncKeyState(key.0 as _) } < 0;
+
+        if code == HC_ACTION as i32 && wparam.0 as u32 == WM_MOUSEWHEEL && is_pressed(VK_MENU) {
+            let info = unsafe { &*(lparam.0 as *const MSLLHOOKSTRUCT) };
+            // The high word is the distance, which is `WHEEL_DELTA` for a notch.
+            let delta = (info.mouseData >> 16) as i16;
+
+            let event = WheelEvent {
+                position: dpi::PhysicalPosition::new(info.pt.x, info.pt.y).into(),
+                notches: delta as f32 / WHEEL_DELTA as f32,
+                shift: is_pressed(VK_SHIFT),
+                control: is_pressed(VK_CONTROL),
+            };
+            WHEEL_CALLBACK.with_borrow(|callback| {
+                if let Some(callback) = callback {
+                    callback(event);
+                }
+            });
+
+            // Press an unassigned key so that the focused application does not open its menu
+            // when Alt is released without any other key.
+            let input = |flags| INPUT {
+                r#type: INPUT_KEYBOARD,
+                Anonymous: INPUT_0 {
+                    ki: KEYBDINPUT {
+                        wVk: VIRTUAL_KEY(0xE8),
+                        dwFlags: flags,
+                        ..Default::default()
+                    },
+                },
+            };
+            let inputs = [input(KEYBD_EVENT_FLAGS(0)), input(KEYEVENTF_KEYUP)];
+            unsafe { SendInput(&inputs, size_of::<INPUT>() as _) };
+
+            return LRESULT(1);
+        }
+
+        unsafe { CallNextHookEx(None, code, wparam, lparam) }
+    }
+
+    /// Handle the messages of this thread until `stopped` is set.
+    fn run_message_loop(stopped: &AtomicBool, mut on_message: impl FnMut(&MSG)) {
+        let timeout = super::INPUT_POLLING_INTERVAL.as_millis() as u32;
+
+        while !stopped.load(Ordering::Relaxed) {
+            unsafe { MsgWaitForMultipleObjects(None, false, timeout, QS_ALLINPUT) };
+
+            let mut message = MSG::default();
+            while unsafe { PeekMessageW(&mut message, None, 0, 0, PM_REMOVE) }.as_bool() {
+                on_message(&message);
+            }
+        }
+    }
+
+    pub fn listen_wheel(callback: impl Fn(WheelEvent) + Send + 'static) -> anyhow::Result<Tracker> {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let tracker = Tracker::spawn(move |stopped| {
+            // The low-level hook is called on this thread while it handles the messages.
+            let hook = match unsafe { SetWindowsHookExW(WH_MOUSE_LL, Some(wheel_hook), None, 0) } {
+                Ok(hook) => hook,
+                Err(error) => {
+                    _ = tx.send(Err(error));
+                    return;
+                }
+            };
+            _ = tx.send(Ok(()));
+            WHEEL_CALLBACK.set(Some(Box::new(callback)));
+
+            run_message_loop(stopped, |_| {});
+
+            _ = unsafe { UnhookWindowsHookEx(hook) };
+        });
+
+        rx.recv()?.context("Failed to hook the mouse wheel")?;
+        Ok(tracker)
+    }
+
+    pub fn register_hotkey(
+        hotkey: &KeyStroke,
+        callback: impl Fn() + Send + 'static,
+    ) -> anyhow::Result<Tracker> {
+        const HOTKEY_ID: i32 = 1;
+
+        let key = (0x08..=0xFE)
+            .find(|&key| {
+                matches!(key_from_virtual_key(key), Some(Key::Other(name)) if name.eq_ignore_ascii_case(&hotkey.key))
+            })
+            .with_context(|| format!("`{}` is not a key", hotkey.key))?;
+        // The key held down does not repeat the shortcut.
+        let modifiers = hotkey
+            .modifiers
+            .iter()
+            .fold(MOD_NOREPEAT, |modifiers, modifier| {
+                modifiers
+                    | match modifier {
+                        Modifier::Control => MOD_CONTROL,
+                        Modifier::Alt => MOD_ALT,
+                        Modifier::Shift => MOD_SHIFT,
+                        Modifier::Super => MOD_WIN,
+                    }
+            });
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tracker = Tracker::spawn(move |stopped| {
+            // `WM_HOTKEY` is posted to the thread which registered the hotkey.
+            if let Err(error) = unsafe { RegisterHotKey(None, HOTKEY_ID, modifiers, key as _) } {
+                _ = tx.send(Err(error));
+                return;
+            }
+            _ = tx.send(Ok(()));
+
+            run_message_loop(stopped, |message| {
+                if message.message == WM_HOTKEY {
+                    callback();
+                }
+            });
+
+            _ = unsafe { UnregisterHotKey(None, HOTKEY_ID) };
+        });
+
+        rx.recv()?
+            .with_context(|| format!("Another application has taken `{hotkey}`"))?;
+        Ok(tracker)
+    }
+
+    fn key_from_virtual_key(key: u16) -> Option<Key> {
+        let name = match key {
+            // The left and right modifiers are also reported by these keys.
+            0x10 => return Some(Key::Modifier(Modifier::Shift)),
+            0x11 => return Some(Key::Modifier(Modifier::Control)),
+            0x12 => return Some(Key::Modifier(Modifier::Alt)),
+            0x5B | 0x5C => return Some(Key::Modifier(Modifier::Super)),
+            0x30..=0x39 | 0x41..=0x5A => char::from(key as u8).to_string(),
+            0x70..=0x7B => format!("F{}", key - 0x6F),
+            0x08 => "Backspace".to_owned(),
+            0x09 => "Tab".to_owned(),
+            0x0D => "Enter".to_owned(),
+            0x1B => "Esc".to_owned(),
+            0x20 => "Space".to_owned(),
+            0x21 => "PgUp".to_owned(),
+            0x22 => "PgDn".to_owned(),
+            0x23 => "End".to_owned(),
+            0x24 => "Home".to_owned(),
+            0x25 => "←".to_owned(),
+            0x26 => "↑".to_owned(),
+            0x27 => "→".to_owned(),
+            0x28 => "↓".to_owned(),
+            0x2D => "Insert".to_owned(),
+            0x2E => "Delete".to_owned(),
+            _ => return None,
+        };
+
+        Some(Key::Other(name))
+    }
+
+    pub fn listen_keys(callback: impl Fn(KeyStroke) + Send + 'static) -> anyhow::Result<Tracker> {
+        Ok(super::spawn_key_listener(
+            || {
+                let pressed = (0x08..=0xFE)
+                    .filter(|&key| unsafe { GetAsyncKeyState(key as _) } < 0)
+                    .filter_map(key_from_virtual_key)
+                    .collect();
+
+                Some(pressed)
+            },
+            callback,
+        ))
+    }
+
+    pub fn capture_screen(
+        origin: dpi::LogicalPosition<f64>,
+        size: dpi::LogicalSize<f64>,
+        scale_factor: f64,
+    ) -> anyhow::Result<CapturedImage> {
+        let origin: dpi::PhysicalPosition<i32> = origin.to_physical(scale_factor);
+        let size: dpi::PhysicalSize<u32> = size.to_physical(scale_factor);
+        let (width, height) = (size.width as i32, size.height as i32);
+        let mut data = vec![0u8; size.width as usize * size.height as usize * 4];
+
+        unsafe {
+            let screen_dc = GetDC(None);
+            let memory_dc = CreateCompatibleDC(Some(screen_dc));
+            let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
+            let previous = SelectObject(memory_dc, bitmap.into());
+
+            let result = BitBlt(
+                memory_dc,
+                0,
+                0,
+                width,
+                height,
+                Some(screen_dc),
+                origin.x,
+                origin.y,
+                SRCCOPY | CAPTUREBLT,
+            );
+
+            // The negative height makes the rows top-down.
+            let mut info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: size_of::<BITMAPINFOHEADER>() as _,
+                    biWidth: width,
+                    biHeight: -height,
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let lines = GetDIBits(
+                memory_dc,
+                bitmap,
+                0,
+                height as _,
+                Some(data.as_mut_ptr().cast()),
+                &mut info,
+                DIB_RGB_COLORS,
+            );
+
+            SelectObject(memory_dc, previous);
+            _ = DeleteObject(bitmap.into());
+            _ = DeleteDC(memory_dc);
+            ReleaseDC(None, screen_dc);
+
+            result?;
+            if lines == 0 {
+                anyhow::bail!("Failed to get the pixels of the screen");
+            }
+        }
+
+        // The alpha channel of the screen is not meaningful.
+        for pixel in data.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+
+        Ok(CapturedImage {
+            width: size.width,
+            height: size.height,
+            data,
+        })
+    }
+
+    thread_local! {
+        /// The callback of the pen hook, which is called on the main thread.
+        static PEN_CALLBACK: RefCell<Option<Box<dyn Fn(PenState)>>> = const { RefCell::new(None) };
+    }
+
+    /// Read the stylus from the pointer messages of our windows before they are handled.
+    unsafe extern "system" fn pen_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        let message = unsafe { &*(lparam.0 as *const MSG) };
+
+        // The message which is only peeked comes again when it is removed.
+        if code == HC_ACTION as i32
+            && wparam.0 as u32 == PM_REMOVE.0
+            && matches!(
+                message.message,
+                WM_POINTERUPDATE | WM_POINTERDOWN | WM_POINTERUP
+            )
+        {
+            // The low word is the ID of the pointer.
+            let id = message.wParam.0 as u32 & 0xFFFF;
+            let mut pointer_type = POINTER_INPUT_TYPE::default();
+            let mut info = POINTER_PEN_INFO::default();
+
+            if unsafe { GetPointerType(id, &mut pointer_type) }.is_ok()
+                && pointer_type == PT_PEN
+                && unsafe { GetPointerPenInfo(id, &mut info) }.is_ok()
+            {
+                // The pressure is from 0 to 1024 and the tilt is from -90 to 90 degrees.
+                let tilt = (info.penMask & (PEN_MASK_TILT_X | PEN_MASK_TILT_Y) != 0)
+                    .then(|| (info.tiltX as f32 / 90., info.tiltY as f32 / 90.));
+                let state = PenState {
+                    pressure: info.pressure as f32 / 1024.,
+                    tilt,
+                    // The stylus is inverted while the eraser end is near the tablet.
+                    eraser: info.penFlags & (PEN_FLAG_INVERTED | PEN_FLAG_ERASER) != 0,
+                };
+
+                PEN_CALLBACK.with_borrow(|callback| {
+                    if let Some(callback) = callback {
+                        callback(state);
+                    }
+                });
+            }
+        }
+
+        unsafe { CallNextHookEx(None, code, wparam, lparam) }
+    }
+
+    /// Listen to the stylus on the canvas windows. This must be called on the main thread,
+    /// where the pointer messages of the windows are handled.
+    pub fn listen_pen(callback: impl Fn(PenState) + Send + 'static) -> anyhow::Result<Tracker> {
+        let hook =
+            unsafe { SetWindowsHookExW(WH_GETMESSAGE, Some(pen_hook), None, GetCurrentThreadId()) }
+                .context("Failed to hook the pointer messages")?;
+        PEN_CALLBACK.set(Some(Box::new(callback)));
+
+        Ok(Tracker::on_current_thread(move || {
+            _ = unsafe { UnhookWindowsHookEx(hook) };
+            PEN_CALLBACK.set(None);
+        }))
+    }
 }
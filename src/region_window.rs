@@ -0,0 +1,39 @@
+use display_config::Display;
+use gpui::{AnyWindowHandle, App, AppContext, Bounds, Pixels};
+
+use crate::{canvas_window, platform_impl::WindowExt, ui_region::RegionSelectView};
+
+/// The window to select the region of the display which the canvas covers.
+pub struct RegionWindow {
+    window_handle: AnyWindowHandle,
+}
+
+impl RegionWindow {
+    pub fn new(cx: &mut App, display: &Display, region: Option<Bounds<Pixels>>) -> Self {
+        let window_options = canvas_window::overlay_window_options(display.origin, display.size);
+        let display_id = display.id.clone();
+
+        #[cfg(target_os = "windows")]
+        let (origin, size) = (display.origin, display.size);
+
+        let window_handle = *cx
+            .open_window(window_options, move |window, cx| {
+                // The region window should be placed on the same level as canvas windows.
+                window.setup_canvas_window();
+
+                #[cfg(target_os = "windows")]
+                canvas_window::set_window_rect(window, origin, size);
+
+                cx.new(|_| RegionSelectView::new(display_id, region))
+            })
+            .expect("Failed to open region window");
+
+        Self { window_handle }
+    }
+
+    pub fn close(&self, cx: &mut App) {
+        self.window_handle
+            .update(cx, |_, window, _| window.remove_window())
+            .unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use gpui::{App, Bounds, Global, Pixels, ReadGlobal, UpdateGlobal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    canvas::CanvasPath, canvas_orchestrator::CanvasOrchestrator,
+    canvas_window_manager::CanvasWindowManager, sticker::StickerSource, utils,
+};
+
+/// The annotations saved to a file, so that they can be prepared before a presentation.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Session {
+    /// The canvases keyed by `utils::display_key`.
+    canvases: BTreeMap<String, SavedCanvas>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedCanvas {
+    paths: Vec<CanvasPath>,
+    stickers: Vec<SavedSticker>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedSticker {
+    source: StickerSource,
+    bounds: Bounds<Pixels>,
+}
+
+/// The session file given on the command line.
+pub struct SessionFile(PathBuf);
+
+impl Global for SessionFile {}
+
+impl SessionFile {
+    /// Put the annotations in the file on the canvases, and save them back when quitting.
+    /// It should be called after the canvas windows are opened.
+    pub fn register_global(cx: &mut App, path: PathBuf) {
+        if path.exists()
+            && let Err(error) = load(cx, &path)
+        {
+            eprintln!("Failed to load the session: {error:#}");
+        }
+
+        cx.on_app_quit(|cx| {
+            if let Err(error) = Self::save(cx) {
+                eprintln!("Failed to save the session: {error:#}");
+            }
+
+            async {}
+        })
+        .detach();
+
+        cx.set_global(Self(path));
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Write the visible annotations to the session file.
+    pub fn save(cx: &mut App) -> anyhow::Result<()> {
+        let path = Self::global(cx).0.clone();
+        save(cx, &path)
+    }
+}
+
+/// Write the visible annotations to `path`.
+pub fn save(cx: &mut App, path: &Path) -> anyhow::Result<()> {
+    let session = snapshot(cx);
+
+    let content = serde_json::to_string(&session).context("Failed to serialize the session")?;
+    std::fs::write(path, content).with_context(|| format!("Failed to write `{}`", path.display()))
+}
+
+fn load(cx: &mut App, path: &Path) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read `{}`", path.display()))?;
+    let mut session: Session = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse `{}`", path.display()))?;
+
+    let display_ids: Vec<_> = CanvasWindowManager::global(cx)
+        .displays()
+        .into_iter()
+        .map(|display| display.id.clone())
+        .collect();
+
+    CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+        for display_id in display_ids {
+            let Some(saved) = session.canvases.remove(&utils::display_key(&display_id)) else {
+                continue;
+            };
+
+            orchestrator.update_canvas(cx, &display_id, |canvas, cx| {
+                for path in saved.paths {
+                    canvas.restore_path(path);
+                }
+
+                for sticker in saved.stickers {
+                    match sticker.source.load(cx) {
+                        Ok(image) => canvas.restore_sticker(sticker.source, image, sticker.bounds),
+                        Err(error) => eprintln!("Failed to load the sticker: {error:#}"),
+                    }
+                }
+
+                cx.notify();
+            });
+        }
+    });
+
+    Ok(())
+}
+
+fn snapshot(cx: &mut App) -> Session {
+    let display_ids: Vec<_> = CanvasWindowManager::global(cx)
+        .displays()
+        .into_iter()
+        .map(|display| display.id.clone())
+        .collect();
+
+    let mut session = Session::default();
+    CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+        for display_id in display_ids {
+            orchestrator.update_canvas(cx, &display_id, |canvas, _| {
+                let stickers = canvas
+                    .visible_stickers()
+                    .into_iter()
+                    .map(|sticker| SavedSticker {
+                        source: sticker.source,
+                        bounds: sticker.bounds,
+                    })
+                    .collect();
+
+                session.canvases.insert(
+                    utils::display_key(&display_id),
+                    SavedCanvas {
+                        paths: canvas.visible_paths(),
+                        stickers,
+                    },
+                );
+            });
+        }
+    });
+
+    session
+}
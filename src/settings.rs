@@ -0,0 +1,557 @@
+use std::{collections::BTreeSet, ops::RangeInclusive, path::PathBuf, time::Duration};
+
+use anyhow::Context as _;
+use display_config::DisplayId;
+use gpui::{App, Global, Hsla, ReadGlobal, Task, UpdateGlobal};
+use serde::{Deserialize, Serialize};
+
+use crate::{canvas_tool::Tool, utils};
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub tools: ToolSettings,
+    pub toolbar: ToolbarSettings,
+    pub displays: DisplaySettings,
+    pub highlight: HighlightSettings,
+    pub spotlight: SpotlightSettings,
+    pub magnifier: MagnifierSettings,
+    pub ripple: RippleSettings,
+    pub keystroke: KeystrokeSettings,
+    pub startup: StartupSettings,
+    pub tray: TraySettings,
+    pub shortcuts: ShortcutSettings,
+    /// Writing the settings file after the changes stop.
+    #[serde(skip)]
+    save_task: Option<Task<()>>,
+    /// The problems found in the settings file at startup, which are shown to the user.
+    #[serde(skip)]
+    problems: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolSettings {
+    /// The tool chosen at startup.
+    pub default_tool: Tool,
+    /// The color of the pen and the stamps at startup.
+    pub color: Hsla,
+    pub pen_width: f32,
+    pub eraser_radius: f32,
+}
+
+impl ToolSettings {
+    pub const PEN_WIDTH_RANGE: RangeInclusive<f32> = 1.0..=30.0;
+    pub const ERASER_RADIUS_RANGE: RangeInclusive<f32> = 4.0..=100.0;
+
+    pub fn set_pen_width(&mut self, width: f32) {
+        self.pen_width = width.clamp(*Self::PEN_WIDTH_RANGE.start(), *Self::PEN_WIDTH_RANGE.end());
+    }
+
+    pub fn set_eraser_radius(&mut self, radius: f32) {
+        self.eraser_radius = radius.clamp(
+            *Self::ERASER_RADIUS_RANGE.start(),
+            *Self::ERASER_RADIUS_RANGE.end(),
+        );
+    }
+}
+
+impl Default for ToolSettings {
+    fn default() -> Self {
+        Self {
+            default_tool: Tool::Cursor,
+            color: gpui::blue(),
+            pen_width: 3.,
+            eraser_radius: 20.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolbarLayout {
+    #[default]
+    Horizontal,
+    /// The buttons are arranged in columns to put the window along the side of the display.
+    Vertical,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolbarSettings {
+    /// The position of the main window where it is moved last.
+    /// If it is `None` or out of the displays, the window is centered.
+    pub position: Option<(f32, f32)>,
+    pub layout: ToolbarLayout,
+    /// Whether only the chosen tool is shown.
+    pub collapsed: bool,
+    /// Whether the window sticks to the edge of the display when it is moved near it.
+    pub snap_to_edges: bool,
+}
+
+impl Default for ToolbarSettings {
+    fn default() -> Self {
+        Self {
+            position: None,
+            layout: ToolbarLayout::Horizontal,
+            collapsed: false,
+            snap_to_edges: true,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    /// Displays which are not covered by a canvas window.
+    pub disabled: BTreeSet<String>,
+    /// The only displays covered while running, given on the command line.
+    #[serde(skip)]
+    pub only: Option<BTreeSet<String>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HighlightStyle {
+    Filled,
+    Ring,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighlightSettings {
+    pub radius: f32,
+    pub color: Hsla,
+    pub opacity: f32,
+    pub style: HighlightStyle,
+    /// The offset added to the cursor position.
+    /// Without it, the highlight gets a little off on some platforms.
+    pub offset_x: f32,
+    pub offset_y: f32,
+}
+
+impl HighlightSettings {
+    pub const RADIUS_RANGE: RangeInclusive<f32> = 4.0..=100.0;
+    pub const OPACITY_RANGE: RangeInclusive<f32> = 0.1..=1.0;
+
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius.clamp(*Self::RADIUS_RANGE.start(), *Self::RADIUS_RANGE.end());
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(*Self::OPACITY_RANGE.start(), *Self::OPACITY_RANGE.end());
+    }
+}
+
+impl Default for HighlightSettings {
+    fn default() -> Self {
+        let offset = if cfg!(target_os = "windows") { 5. } else { 0. };
+
+        Self {
+            radius: 20.,
+            color: gpui::red(),
+            opacity: 0.4,
+            style: HighlightStyle::Filled,
+            offset_x: offset,
+            offset_y: offset,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpotlightShape {
+    Circle,
+    Rectangle,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpotlightSettings {
+    /// The radius of the circle, or the half height of the rectangle.
+    pub radius: f32,
+    pub shape: SpotlightShape,
+    /// The opacity of the dimmed area.
+    pub opacity: f32,
+}
+
+impl SpotlightSettings {
+    pub const RADIUS_RANGE: RangeInclusive<f32> = 30.0..=800.0;
+    pub const OPACITY_RANGE: RangeInclusive<f32> = 0.1..=0.95;
+
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius.clamp(*Self::RADIUS_RANGE.start(), *Self::RADIUS_RANGE.end());
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(*Self::OPACITY_RANGE.start(), *Self::OPACITY_RANGE.end());
+    }
+}
+
+impl Default for SpotlightSettings {
+    fn default() -> Self {
+        Self {
+            radius: 150.,
+            shape: SpotlightShape::Circle,
+            opacity: 0.6,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MagnifierSettings {
+    pub zoom: f32,
+    /// The radius of the lens.
+    pub radius: f32,
+}
+
+impl MagnifierSettings {
+    pub const ZOOM_RANGE: RangeInclusive<f32> = 1.5..=8.0;
+    pub const RADIUS_RANGE: RangeInclusive<f32> = 40.0..=300.0;
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(*Self::ZOOM_RANGE.start(), *Self::ZOOM_RANGE.end());
+    }
+
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius.clamp(*Self::RADIUS_RANGE.start(), *Self::RADIUS_RANGE.end());
+    }
+}
+
+impl Default for MagnifierSettings {
+    fn default() -> Self {
+        Self {
+            zoom: 2.,
+            radius: 100.,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RippleSettings {
+    /// Whether a ripple is drawn at every click.
+    pub enabled: bool,
+    pub left_color: Hsla,
+    pub right_color: Hsla,
+}
+
+impl Default for RippleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            left_color: gpui::blue(),
+            right_color: gpui::red(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeystrokeSettings {
+    /// Whether the pressed keys are shown on the display.
+    pub enabled: bool,
+    /// The display to show the keys. If it is `None`, the primary display is used.
+    pub display: Option<String>,
+    pub corner: Corner,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StartupSettings {
+    /// Whether the controller window is hidden at startup, as with `--hidden`.
+    pub hidden: bool,
+    /// Whether the controller window is opened where it is moved last.
+    pub restore_position: bool,
+}
+
+impl Default for StartupSettings {
+    fn default() -> Self {
+        Self {
+            hidden: false,
+            restore_position: true,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraySettings {
+    /// Whether the icon is put in the system tray, or in the status bar on macOS.
+    pub enabled: bool,
+    /// Whether closing the controller window quits the application.
+    /// If it is false, the window is hidden and can be shown again from the tray.
+    pub quit_on_close: bool,
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            quit_on_close: true,
+        }
+    }
+}
+
+/// The keys which work while another application is focused, e.g. `Ctrl + Shift + H`.
+///
+/// None is set by default, since the global keys are taken away from the other applications.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutSettings {
+    /// Hide or show the annotations on all canvases.
+    pub toggle_annotations: Option<String>,
+}
+
+impl Global for Settings {}
+
+impl Settings {
+    /// How long to wait for more changes before writing the settings file.
+    const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+    pub fn register_global(cx: &mut App) {
+        let mut problems = Vec::new();
+        let mut settings = Self::load().unwrap_or_else(|error| {
+            problems.push(format!(
+                "Failed to load the settings, so the default settings is used: {error:#}"
+            ));
+            problems.extend(Self::backup_broken_file());
+
+            Self::default()
+        });
+
+        problems.extend(settings.validate());
+        for problem in &problems {
+            eprintln!("Settings: {problem}");
+        }
+        settings.problems = problems;
+
+        // Write the changes which are still waiting for the delay.
+        cx.on_app_quit(|cx| {
+            if cx.global_mut::<Self>().save_task.take().is_some() {
+                Self::global(cx).save_or_report();
+            }
+
+            async {}
+        })
+        .detach();
+
+        cx.set_global(settings);
+    }
+
+    /// Keep the file which could not be loaded, so that it is not overwritten by the next save.
+    /// Returns the message telling where it is kept.
+    fn backup_broken_file() -> Option<String> {
+        let path = Self::path().ok().filter(|path| path.exists())?;
+        let backup = path.with_extension("toml.bak");
+
+        Some(match std::fs::rename(&path, &backup) {
+            Ok(()) => format!(
+                "The broken settings file is moved to `{}`",
+                backup.display()
+            ),
+            Err(error) => format!("Failed to back up the broken settings file: {error}"),
+        })
+    }
+
+    /// Clamp the values out of their ranges, and return the messages describing them.
+    fn validate(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut check = |name: &str, value: &mut f32, range: RangeInclusive<f32>| {
+            if !range.contains(value) {
+                errors.push(format!(
+                    "`{name}` should be between {} and {}, but it is {value}",
+                    range.start(),
+                    range.end(),
+                ));
+
+                *value = if value.is_nan() {
+                    *range.start()
+                } else {
+                    value.clamp(*range.start(), *range.end())
+                };
+            }
+        };
+
+        check(
+            "tools.pen_width",
+            &mut self.tools.pen_width,
+            ToolSettings::PEN_WIDTH_RANGE,
+        );
+        check(
+            "tools.eraser_radius",
+            &mut self.tools.eraser_radius,
+            ToolSettings::ERASER_RADIUS_RANGE,
+        );
+        check(
+            "highlight.radius",
+            &mut self.highlight.radius,
+            HighlightSettings::RADIUS_RANGE,
+        );
+        check(
+            "highlight.opacity",
+            &mut self.highlight.opacity,
+            HighlightSettings::OPACITY_RANGE,
+        );
+        check(
+            "spotlight.radius",
+            &mut self.spotlight.radius,
+            SpotlightSettings::RADIUS_RANGE,
+        );
+        check(
+            "spotlight.opacity",
+            &mut self.spotlight.opacity,
+            SpotlightSettings::OPACITY_RANGE,
+        );
+        check(
+            "magnifier.zoom",
+            &mut self.magnifier.zoom,
+            MagnifierSettings::ZOOM_RANGE,
+        );
+        check(
+            "magnifier.radius",
+            &mut self.magnifier.radius,
+            MagnifierSettings::RADIUS_RANGE,
+        );
+
+        errors
+    }
+
+    fn path() -> anyhow::Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not find the config directory")?;
+        Ok(config_dir.join(crate::APP_IDENTIFIER).join("settings.toml"))
+    }
+
+    fn load() -> anyhow::Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read `{}`", path.display()))?;
+
+        toml::from_str(&content).with_context(|| format!("Failed to parse `{}`", path.display()))
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path()?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create `{}`", parent.display()))?;
+        }
+
+        let content = toml::to_string_pretty(self).context("Failed to serialize the settings")?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write `{}`", path.display()))
+    }
+
+    fn save_or_report(&self) {
+        if let Err(error) = self.save() {
+            eprintln!("Failed to save the settings: {error:#}");
+        }
+    }
+
+    /// Update the settings and write them to the settings file.
+    /// The changes in a row, e.g. while the controller is dragged, are written at once.
+    pub fn update(cx: &mut App, f: impl FnOnce(&mut Self)) {
+        Self::update_global(cx, |settings, cx| {
+            f(settings);
+
+            // Replacing the task cancels the save waiting for the previous change.
+            settings.save_task = Some(cx.spawn(async move |cx| {
+                cx.background_executor().timer(Self::SAVE_DELAY).await;
+                _ = cx.update(|cx| Self::global(cx).save_or_report());
+            }));
+        });
+    }
+
+    /// Get the problems found in the settings file at startup.
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+
+    pub fn dismiss_problems(cx: &mut App) {
+        Self::update_global(cx, |settings, _| settings.problems.clear());
+    }
+
+    pub fn is_display_enabled(&self, display_id: &DisplayId) -> bool {
+        let key = utils::display_key(display_id);
+
+        match &self.displays.only {
+            Some(only) => only.contains(&key),
+            None => !self.displays.disabled.contains(&key),
+        }
+    }
+
+    pub fn set_display_enabled(&mut self, display_id: &DisplayId, enabled: bool) {
+        let key = utils::display_key(display_id);
+
+        if let Some(only) = self.displays.only.as_mut() {
+            if enabled {
+                only.insert(key.clone());
+            } else {
+                only.remove(&key);
+            }
+        }
+
+        if enabled {
+            self.displays.disabled.remove(&key);
+        } else {
+            self.displays.disabled.insert(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_default_settings() {
+        let mut settings = Settings::default();
+        assert!(settings.validate().is_empty());
+    }
+
+    #[test]
+    fn clamp_values_out_of_range() {
+        let mut settings: Settings = toml::from_str(
+            r#"
+            [tools]
+            pen_width = 0.0
+            eraser_radius = 1000.0
+
+            [spotlight]
+            opacity = 2.0
+
+            [magnifier]
+            zoom = nan
+            "#,
+        )
+        .unwrap();
+
+        let errors = settings.validate();
+        assert_eq!(errors.len(), 4, "{errors:?}");
+        assert!(errors[0].contains("tools.pen_width"), "{errors:?}");
+
+        assert_eq!(settings.tools.pen_width, 1.0);
+        assert_eq!(settings.tools.eraser_radius, 100.0);
+        assert_eq!(settings.spotlight.opacity, 0.95);
+        assert_eq!(settings.magnifier.zoom, 1.5);
+
+        // The clamped values are valid, so they are not reported again.
+        assert!(settings.validate().is_empty());
+    }
+}
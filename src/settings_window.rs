@@ -0,0 +1,57 @@
+use gpui::{AnyWindowHandle, App, AppContext, Global, Pixels, ReadGlobal, Size, px, size};
+
+use crate::{settings::Settings, ui_settings::SettingsView};
+
+/// The window to edit the settings. It is opened on demand.
+#[derive(Default)]
+pub struct SettingsWindow(Option<AnyWindowHandle>);
+
+impl Global for SettingsWindow {}
+
+impl SettingsWindow {
+    pub const SIZE: Size<Pixels> = size(px(360.), px(560.));
+
+    /// It should be called after the settings are loaded.
+    pub fn register_global(cx: &mut App) {
+        let mut window = Self::default();
+
+        // Tell the problems in the settings file, since stderr is not shown on Windows.
+        if !Settings::global(cx).problems().is_empty() {
+            window.open(cx);
+        }
+
+        cx.set_global(window);
+    }
+
+    /// Open the settings window, or bring it to the front if it is already opened.
+    pub fn open(&mut self, cx: &mut App) {
+        if let Some(handle) = self.0
+            && handle
+                .update(cx, |_, window, _| window.activate_window())
+                .is_ok()
+        {
+            return;
+        }
+
+        let bounds = gpui::Bounds::centered(None, Self::SIZE, cx);
+        let window_options = gpui::WindowOptions {
+            titlebar: Some(gpui::TitlebarOptions {
+                title: Some("Monica - Settings".into()),
+                ..Default::default()
+            }),
+            window_bounds: Some(gpui::WindowBounds::Windowed(bounds)),
+            is_resizable: false,
+            app_id: Some(crate::APP_IDENTIFIER.to_owned()),
+            ..Default::default()
+        };
+
+        let handle = cx
+            .open_window(window_options, |window, cx| {
+                let view = cx.new(SettingsView::new);
+                cx.new(|cx| gpui_component::Root::new(view, window, cx))
+            })
+            .expect("Failed to open the settings window");
+
+        self.0 = Some(*handle);
+    }
+}
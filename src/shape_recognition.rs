@@ -0,0 +1,412 @@
+//! Turn the freehand strokes roughly forming a shape into the clean shape.
+
+use gpui::{Point, point};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Line {
+        start: Point<f32>,
+        end: Point<f32>,
+    },
+    /// The line with the head at `end`.
+    Arrow {
+        start: Point<f32>,
+        end: Point<f32>,
+    },
+    /// The circle or the ellipse.
+    Ellipse {
+        center: Point<f32>,
+        radius: Point<f32>,
+    },
+    Rectangle {
+        min: Point<f32>,
+        max: Point<f32>,
+    },
+    Triangle([Point<f32>; 3]),
+}
+
+/// The trail smaller than this is left as it is, e.g. a dot.
+const MIN_SIZE: f32 = 16.;
+/// The trail is straight if the distance between its ends is this ratio of its length.
+const STRAIGHTNESS: f32 = 0.95;
+/// The trail is closed if the gap between its ends is less than this ratio of its length.
+const CLOSED_GAP: f32 = 0.15;
+/// The tolerance to simplify the trail into the corners, relative to the size of the trail.
+const SIMPLIFY_TOLERANCE: f32 = 0.08;
+/// The average deviation from the ellipse relative to its radius.
+const ELLIPSE_TOLERANCE: f32 = 0.1;
+/// The angle which the edges of a rectangle may lean.
+const MAX_LEAN: f32 = std::f32::consts::PI / 9.;
+/// The angle between the shaft and the wing of an arrow head.
+const HEAD_ANGLE: f32 = std::f32::consts::PI / 6.;
+
+/// Find the shape which the trail roughly forms.
+pub fn recognize(trail: &[Point<f32>]) -> Option<Shape> {
+    let (first, last) = (*trail.first()?, *trail.last()?);
+    let (min, max) = bounds(trail);
+    let size = distance(min, max);
+    if size < MIN_SIZE {
+        return None;
+    }
+
+    let length: f32 = trail
+        .windows(2)
+        .map(|pair| distance(pair[0], pair[1]))
+        .sum();
+    let gap = distance(first, last);
+    let tolerance = size * SIMPLIFY_TOLERANCE;
+
+    if gap < length * CLOSED_GAP {
+        recognize_closed(trail, tolerance)
+    } else if gap > length * STRAIGHTNESS {
+        Some(Shape::Line {
+            start: first,
+            end: last,
+        })
+    } else {
+        recognize_arrow(trail, tolerance)
+    }
+}
+
+/// The arrow is drawn as the shaft followed by the head near its tip,
+/// e.g. `start → tip → wing → tip → wing`.
+fn recognize_arrow(trail: &[Point<f32>], tolerance: f32) -> Option<Shape> {
+    let corners = simplify(trail, tolerance);
+    let [start, tip, head @ ..] = corners.as_slice() else {
+        return None;
+    };
+    let shaft = distance(*start, *tip);
+
+    if head.is_empty() || head.len() > 3 {
+        return None;
+    }
+
+    let is_near_tip = head.iter().all(|wing| distance(*wing, *tip) < shaft / 2.);
+    // At least one wing goes back along the shaft.
+    let goes_back = head.iter().any(|wing| {
+        let angle = turn_angle(*wing - *tip, *start - *tip).abs();
+        distance(*wing, *tip) > tolerance && angle < HEAD_ANGLE * 2.5
+    });
+
+    (is_near_tip && goes_back).then_some(Shape::Arrow {
+        start: *start,
+        end: *tip,
+    })
+}
+
+fn recognize_closed(trail: &[Point<f32>], tolerance: f32) -> Option<Shape> {
+    let corners = simplify_closed(trail, tolerance);
+
+    match corners.as_slice() {
+        [a, b, c] => Some(Shape::Triangle([*a, *b, *c])),
+        [_, _, _, _] if is_upright_rectangle(&corners) => {
+            let (min, max) = bounds(&corners);
+            Some(Shape::Rectangle { min, max })
+        }
+        _ => fit_ellipse(trail),
+    }
+}
+
+fn is_upright_rectangle(corners: &[Point<f32>]) -> bool {
+    let edges: Vec<_> = (0..corners.len())
+        .map(|i| corners[(i + 1) % corners.len()] - corners[i])
+        .collect();
+    let is_horizontal = |edge: Point<f32>| edge.x.abs() > edge.y.abs();
+
+    edges.iter().enumerate().all(|(i, edge)| {
+        let lean = edge
+            .x
+            .abs()
+            .min(edge.y.abs())
+            .atan2(edge.x.abs().max(edge.y.abs()));
+        // The edges are horizontal and vertical in turn.
+        let next = edges[(i + 1) % edges.len()];
+
+        lean < MAX_LEAN && is_horizontal(*edge) != is_horizontal(next)
+    })
+}
+
+fn fit_ellipse(trail: &[Point<f32>]) -> Option<Shape> {
+    let (min, max) = bounds(trail);
+    let center = point((min.x + max.x) / 2., (min.y + max.y) / 2.);
+    let radius = point((max.x - min.x) / 2., (max.y - min.y) / 2.);
+    if radius.x <= 0. || radius.y <= 0. {
+        return None;
+    }
+
+    let deviation: f32 = trail
+        .iter()
+        .map(|pos| {
+            let x = (pos.x - center.x) / radius.x;
+            let y = (pos.y - center.y) / radius.y;
+            (x.hypot(y) - 1.).abs()
+        })
+        .sum::<f32>()
+        / trail.len() as f32;
+
+    (deviation < ELLIPSE_TOLERANCE).then_some(Shape::Ellipse { center, radius })
+}
+
+impl Shape {
+    /// Get the trail to draw the shape.
+    pub fn points(&self) -> Vec<Point<f32>> {
+        match *self {
+            Self::Line { start, end } => vec![start, end],
+            Self::Arrow { start, end } => {
+                let shaft = end - start;
+                let length = distance(start, end);
+                let head = (length / 4.).clamp(8., 32.);
+                let back = shaft.y.atan2(shaft.x) + std::f32::consts::PI;
+                let wing = |angle: f32| end + point(angle.cos() * head, angle.sin() * head);
+
+                vec![
+                    start,
+                    end,
+                    wing(back + HEAD_ANGLE),
+                    wing(back - HEAD_ANGLE),
+                    end,
+                ]
+            }
+            Self::Ellipse { center, radius } => {
+                const SEGMENTS: usize = 64;
+
+                (0..=SEGMENTS)
+                    .map(|i| {
+                        let angle = std::f32::consts::TAU * i as f32 / SEGMENTS as f32;
+                        center + point(radius.x * angle.cos(), radius.y * angle.sin())
+                    })
+                    .collect()
+            }
+            Self::Rectangle { min, max } => {
+                vec![min, point(max.x, min.y), max, point(min.x, max.y), min]
+            }
+            Self::Triangle([a, b, c]) => vec![a, b, c, a],
+        }
+    }
+}
+
+fn distance(a: Point<f32>, b: Point<f32>) -> f32 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+fn bounds(points: &[Point<f32>]) -> (Point<f32>, Point<f32>) {
+    points.iter().fold(
+        (point(f32::MAX, f32::MAX), point(f32::MIN, f32::MIN)),
+        |(min, max), pos| {
+            (
+                point(min.x.min(pos.x), min.y.min(pos.y)),
+                point(max.x.max(pos.x), max.y.max(pos.y)),
+            )
+        },
+    )
+}
+
+/// Get the angle which the direction turns from `a` to `b`.
+fn turn_angle(a: Point<f32>, b: Point<f32>) -> f32 {
+    (a.x * b.y - a.y * b.x).atan2(a.x * b.x + a.y * b.y)
+}
+
+fn distance_to_segment(pos: Point<f32>, start: Point<f32>, end: Point<f32>) -> f32 {
+    let segment = end - start;
+    let length_sq = segment.x * segment.x + segment.y * segment.y;
+    if length_sq == 0. {
+        return distance(pos, start);
+    }
+
+    let t = ((pos.x - start.x) * segment.x + (pos.y - start.y) * segment.y) / length_sq;
+    distance(pos, start + segment * t.clamp(0., 1.))
+}
+
+/// Reduce the trail to its corners with the Ramer-Douglas-Peucker algorithm.
+fn simplify(trail: &[Point<f32>], tolerance: f32) -> Vec<Point<f32>> {
+    let (first, last) = (trail[0], trail[trail.len() - 1]);
+    if trail.len() < 3 {
+        return trail.to_vec();
+    }
+
+    let (index, farthest) = trail[1..trail.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(i, pos)| (i + 1, distance_to_segment(*pos, first, last)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+
+    if farthest <= tolerance {
+        return vec![first, last];
+    }
+
+    let mut corners = simplify(&trail[..=index], tolerance);
+    corners.pop();
+    corners.extend(simplify(&trail[index..], tolerance));
+
+    corners
+}
+
+/// Reduce the closed trail to its corners. The trail may start in the middle of an edge.
+fn simplify_closed(trail: &[Point<f32>], tolerance: f32) -> Vec<Point<f32>> {
+    // Split the trail at the point farthest from the start, which is surely a corner or so.
+    let index = (0..trail.len())
+        .max_by(|a, b| distance(trail[0], trail[*a]).total_cmp(&distance(trail[0], trail[*b])))
+        .unwrap_or(0);
+    if index == 0 {
+        return Vec::new();
+    }
+
+    let mut corners = simplify(&trail[..=index], tolerance);
+    corners.pop();
+    corners.extend(simplify(&trail[index..], tolerance));
+    // The end is at the start.
+    corners.pop();
+
+    // Remove the points on the edges, such as the start, and the points close to each other.
+    let mut i = 0;
+    while corners.len() > 2 && i < corners.len() {
+        let len = corners.len();
+        let (prev, next) = (corners[(i + len - 1) % len], corners[(i + 1) % len]);
+
+        if distance_to_segment(corners[i], prev, next) <= tolerance {
+            corners.remove(i);
+            i = 0;
+        } else {
+            i += 1;
+        }
+    }
+
+    corners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Make the trail passing the points with a small wobble like a hand.
+    fn trail(points: &[(f32, f32)]) -> Vec<Point<f32>> {
+        const STEPS: usize = 20;
+
+        let mut trail = Vec::new();
+        for pair in points.windows(2) {
+            let (a, b) = (point(pair[0].0, pair[0].1), point(pair[1].0, pair[1].1));
+
+            for step in 0..STEPS {
+                let t = step as f32 / STEPS as f32;
+                let wobble = (trail.len() as f32 * 1.3).sin() * 1.5;
+                trail.push(a + (b - a) * t + point(wobble, -wobble));
+            }
+        }
+
+        let last = points[points.len() - 1];
+        trail.push(point(last.0, last.1));
+
+        trail
+    }
+
+    fn circle(center: (f32, f32), radius: (f32, f32), turn: f32) -> Vec<Point<f32>> {
+        (0..=48)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * turn * i as f32 / 48.;
+                let wobble = 1. + (i as f32 * 2.1).sin() * 0.03;
+
+                point(
+                    center.0 + radius.0 * angle.cos() * wobble,
+                    center.1 + radius.1 * angle.sin() * wobble,
+                )
+            })
+            .collect()
+    }
+
+    fn assert_near(a: Point<f32>, b: (f32, f32)) {
+        assert!(
+            distance(a, point(b.0, b.1)) < 10.,
+            "{a:?} is not near {b:?}"
+        );
+    }
+
+    #[test]
+    fn recognize_line() {
+        let shape = recognize(&trail(&[(10., 10.), (200., 120.)]));
+        assert!(matches!(shape, Some(Shape::Line { .. })), "{shape:?}");
+    }
+
+    #[test]
+    fn recognize_arrow() {
+        let shape = recognize(&trail(&[
+            (0., 100.),
+            (200., 100.),
+            (170., 80.),
+            (200., 100.),
+            (170., 120.),
+        ]));
+
+        let Some(Shape::Arrow { start, end }) = shape else {
+            panic!("{shape:?}");
+        };
+        assert_near(start, (0., 100.));
+        assert_near(end, (200., 100.));
+    }
+
+    #[test]
+    fn recognize_circle_and_ellipse() {
+        let shape = recognize(&circle((100., 100.), (80., 80.), 1.));
+        let Some(Shape::Ellipse { center, radius }) = shape else {
+            panic!("{shape:?}");
+        };
+        assert_near(center, (100., 100.));
+        assert_near(radius, (80., 80.));
+
+        let shape = recognize(&circle((300., 200.), (120., 60.), 1.));
+        assert!(matches!(shape, Some(Shape::Ellipse { .. })), "{shape:?}");
+    }
+
+    #[test]
+    fn recognize_rectangle_started_in_the_middle_of_an_edge() {
+        let shape = recognize(&trail(&[
+            (100., 50.),
+            (250., 50.),
+            (250., 150.),
+            (50., 150.),
+            (50., 50.),
+            (105., 52.),
+        ]));
+
+        let Some(Shape::Rectangle { min, max }) = shape else {
+            panic!("{shape:?}");
+        };
+        assert_near(min, (50., 50.));
+        assert_near(max, (250., 150.));
+    }
+
+    #[test]
+    fn recognize_triangle() {
+        let shape = recognize(&trail(&[
+            (100., 20.),
+            (180., 160.),
+            (20., 160.),
+            (98., 24.),
+        ]));
+
+        let Some(Shape::Triangle(corners)) = shape else {
+            panic!("{shape:?}");
+        };
+        for corner in [(100., 20.), (180., 160.), (20., 160.)] {
+            assert!(
+                corners
+                    .iter()
+                    .any(|pos| distance(*pos, point(corner.0, corner.1)) < 10.),
+                "{corner:?} is not in {corners:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn leave_freehand_strokes() {
+        // A zigzag
+        let zigzag = trail(&[(0., 0.), (40., 80.), (80., 0.), (120., 80.), (160., 0.)]);
+        assert_eq!(recognize(&zigzag), None);
+
+        // A half circle
+        assert_eq!(recognize(&circle((100., 100.), (80., 80.), 0.5)), None);
+
+        // A dot
+        assert_eq!(recognize(&trail(&[(10., 10.), (12., 11.)])), None);
+    }
+}
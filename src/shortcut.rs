@@ -0,0 +1,132 @@
+use anyhow::Context as _;
+use gpui::{App, AsyncApp, Global, ReadGlobal, UpdateGlobal};
+
+use crate::{
+    canvas_orchestrator::CanvasOrchestrator,
+    platform_impl::{self, KeyStroke, Modifier, Tracker},
+    settings::Settings,
+};
+
+/// The shortcut keys which work while another application is focused.
+///
+/// They are registered as the global hotkeys, so the keys do not reach the focused
+/// application, and registered again when they are changed in the settings.
+pub struct Shortcuts {
+    /// The keys of `shortcuts.toggle_annotations` which are registered.
+    toggle_annotations: Option<String>,
+    /// Why the keys could not be registered, which is shown in the settings window.
+    error: Option<String>,
+    /// It is registered while the shortcut is set in the settings.
+    _hotkey: Option<Tracker>,
+}
+
+impl Global for Shortcuts {}
+
+impl Shortcuts {
+    pub fn register_global(cx: &mut App) {
+        cx.set_global(Self {
+            toggle_annotations: None,
+            error: None,
+            _hotkey: None,
+        });
+
+        Self::update(cx);
+        cx.observe_global::<Settings>(Self::update).detach();
+    }
+
+    /// Register the keys again if they are changed in the settings.
+    pub fn update(cx: &mut App) {
+        let keys = Settings::global(cx).shortcuts.toggle_annotations.clone();
+        if Self::global(cx).toggle_annotations == keys {
+            return;
+        }
+
+        // Release the old keys first, so that they can be registered again.
+        Self::update_global(cx, |this, _| this._hotkey = None);
+
+        let (hotkey, error) = match keys.as_deref().map(|keys| Self::register(cx, keys)) {
+            Some(Ok(hotkey)) => (Some(hotkey), None),
+            Some(Err(error)) => {
+                let error = format!("{error:#}");
+                eprintln!("Failed to register the shortcut keys: {error}");
+                (None, Some(error))
+            }
+            None => (None, None),
+        };
+
+        Self::update_global(cx, |this, _| {
+            this.toggle_annotations = keys;
+            this.error = error;
+            this._hotkey = hotkey;
+        });
+    }
+
+    /// Release the keys while new keys are recorded, so that they reach the settings window.
+    /// They are registered again by `update`.
+    pub fn suspend(cx: &mut App) {
+        Self::update_global(cx, |this, _| {
+            this.toggle_annotations = None;
+            this._hotkey = None;
+        });
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    fn register(cx: &mut App, keys: &str) -> anyhow::Result<Tracker> {
+        let hotkey = parse(keys)?;
+
+        let (tx, rx) = async_channel::unbounded();
+        let hotkey = platform_impl::register_hotkey(&hotkey, move || {
+            _ = tx.send_blocking(());
+        })?;
+
+        cx.spawn(async move |cx| Self::listener(cx, rx).await)
+            .detach();
+
+        Ok(hotkey)
+    }
+
+    async fn listener(cx: &mut AsyncApp, rx: async_channel::Receiver<()>) {
+        // The channel is closed when the hotkey is unregistered.
+        while rx.recv().await.is_ok() {
+            let result = cx.update(|cx| {
+                CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+                    orchestrator.toggle_annotations_hidden(cx);
+                });
+            });
+
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Parse the keys such as `Ctrl + Shift + H`, whose modifiers can be written in any order and case.
+fn parse(keys: &str) -> anyhow::Result<KeyStroke> {
+    let mut names: Vec<_> = keys.split('+').map(str::trim).collect();
+    let key = names
+        .pop()
+        .filter(|key| !key.is_empty())
+        .with_context(|| format!("`{keys}` has no key"))?;
+
+    let mut modifiers = names
+        .into_iter()
+        .map(|name| match name.to_lowercase().as_str() {
+            "ctrl" | "control" => Ok(Modifier::Control),
+            "alt" | "option" => Ok(Modifier::Alt),
+            "shift" => Ok(Modifier::Shift),
+            "super" | "win" | "cmd" | "command" => Ok(Modifier::Super),
+            _ => Err(anyhow::anyhow!("`{name}` is not a modifier")),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    modifiers.sort();
+    modifiers.dedup();
+
+    Ok(KeyStroke {
+        modifiers,
+        key: key.to_owned(),
+    })
+}
@@ -0,0 +1,112 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Context as _;
+use gpui::{App, AssetSource, Image, ImageFormat, RenderImage, SharedString};
+use serde::{Deserialize, Serialize};
+
+use crate::icon::Assets;
+
+/// Where the image of a sticker comes from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StickerSource {
+    /// The sticker bundled in the assets, e.g. `stickers/star.svg`.
+    Bundled(SharedString),
+    /// The PNG or SVG file put in the sticker directory by the user.
+    File(PathBuf),
+}
+
+impl StickerSource {
+    /// The directory where the user puts their own stickers.
+    pub fn user_dir() -> anyhow::Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not find the config directory")?;
+        Ok(config_dir.join(crate::APP_IDENTIFIER).join("stickers"))
+    }
+
+    /// List the bundled stickers followed by the user stickers.
+    pub fn list() -> Vec<Self> {
+        let mut sources: Vec<_> = Assets
+            .list("stickers/")
+            .unwrap_or_default()
+            .into_iter()
+            .map(Self::Bundled)
+            .collect();
+
+        let user_files = Self::user_dir().and_then(|dir| Ok(std::fs::read_dir(dir)?));
+        if let Ok(entries) = user_files {
+            let mut paths: Vec<_> = entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| image_format(path.to_str().unwrap_or_default()).is_some())
+                .collect();
+            paths.sort();
+
+            sources.extend(paths.into_iter().map(Self::File));
+        }
+
+        sources
+    }
+
+    /// The path of the image which can be given to `gpui::img`.
+    pub fn image_source(&self) -> gpui::ImageSource {
+        match self {
+            Self::Bundled(path) => path.clone().into(),
+            Self::File(path) => path.clone().into(),
+        }
+    }
+
+    /// Decode the image to paint it on the canvas.
+    pub fn load(&self, cx: &App) -> anyhow::Result<Arc<RenderImage>> {
+        let (name, bytes) = match self {
+            Self::Bundled(path) => {
+                let bytes = Assets
+                    .load(path)?
+                    .with_context(|| format!("could not find asset at path `{path}`"))?;
+
+                (path.to_string(), bytes.into_owned())
+            }
+            Self::File(path) => {
+                let bytes = std::fs::read(path)
+                    .with_context(|| format!("Failed to read `{}`", path.display()))?;
+
+                (path.display().to_string(), bytes)
+            }
+        };
+
+        let format = image_format(&name)
+            .with_context(|| format!("`{name}` is neither a PNG nor an SVG image"))?;
+        let image = Image::from_bytes(format, bytes)
+            .to_image_data(cx.svg_renderer())
+            .with_context(|| format!("Failed to decode `{name}`"))?;
+
+        if format != ImageFormat::Svg {
+            return Ok(image);
+        }
+
+        // The SVG is rendered into premultiplied RGBA while the other formats are decoded into
+        // straight BGRA, so convert it to paint it in the same way as the PNG images.
+        let size = image.size(0);
+        let mut data = image.as_bytes(0).unwrap_or_default().to_vec();
+        for pixel in data.chunks_exact_mut(4) {
+            let color =
+                tiny_skia::PremultipliedColorU8::from_rgba(pixel[0], pixel[1], pixel[2], pixel[3])
+                    .map(|color| color.demultiply())
+                    .unwrap_or(tiny_skia::ColorU8::from_rgba(0, 0, 0, 0));
+
+            pixel.copy_from_slice(&[color.blue(), color.green(), color.red(), color.alpha()]);
+        }
+
+        let buffer = image::RgbaImage::from_raw(size.width.0 as u32, size.height.0 as u32, data)
+            .context("The rendered SVG has an unexpected size")?;
+
+        Ok(Arc::new(RenderImage::new(vec![image::Frame::new(buffer)])))
+    }
+}
+
+fn image_format(name: &str) -> Option<ImageFormat> {
+    let extension = name.rsplit_once('.')?.1.to_ascii_lowercase();
+
+    match extension.as_str() {
+        "png" => Some(ImageFormat::Png),
+        "svg" => Some(ImageFormat::Svg),
+        _ => None,
+    }
+}
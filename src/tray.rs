@@ -0,0 +1,155 @@
+use gpui::{App, AsyncApp, Global, ReadGlobal, UpdateGlobal};
+
+use crate::{
+    canvas::ToolState, canvas_orchestrator::CanvasOrchestrator, canvas_tool::Tool,
+    main_window::MainWindow, settings::Settings,
+};
+
+const ICON: &[u8] = include_bytes!("../icons/32x32.png");
+
+/// The item chosen in the tray menu.
+#[derive(Clone, Copy)]
+enum TrayEvent {
+    ShowController,
+    HideController,
+    SetTool(Tool),
+    Clear,
+    Quit,
+}
+
+/// The icon in the system tray, or in the status bar on macOS.
+pub struct Tray {
+    /// The icon is removed from the tray when it is dropped.
+    _icon: platform::Tray,
+}
+
+impl Global for Tray {}
+
+impl Tray {
+    /// Put the icon in the tray unless it is disabled in the settings.
+    pub fn register_global(cx: &mut App) {
+        if !Settings::global(cx).tray.enabled {
+            return;
+        }
+
+        let (tx, rx) = async_channel::unbounded();
+        match platform::Tray::new(tx) {
+            Ok(icon) => cx.set_global(Self { _icon: icon }),
+            Err(error) => {
+                eprintln!("Failed to put the icon in the tray: {error:#}");
+                return;
+            }
+        }
+
+        cx.spawn(async move |cx| Self::listener(cx, rx).await)
+            .detach();
+    }
+
+    /// Whether the controller window can be closed without quitting the application.
+    pub fn keeps_running(cx: &App) -> bool {
+        cx.has_global::<Self>() && !Settings::global(cx).tray.quit_on_close
+    }
+
+    async fn listener(cx: &mut AsyncApp, rx: async_channel::Receiver<TrayEvent>) {
+        while let Ok(event) = rx.recv().await {
+            let result = cx.update(|cx| match event {
+                TrayEvent::ShowController => {
+                    MainWindow::update_global(cx, |window, cx| window.show(cx));
+                }
+                TrayEvent::HideController => {
+                    MainWindow::update_global(cx, |window, cx| window.set_hidden(cx, true));
+                }
+                TrayEvent::SetTool(tool) => {
+                    ToolState::update_global(cx, |state, cx| state.set_tool(cx, tool));
+                }
+                TrayEvent::Clear => CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+                    orchestrator.clear(cx);
+                }),
+                TrayEvent::Quit => cx.quit(),
+            });
+
+            if result.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Decode the application icon into the straight RGBA pixels.
+fn icon_rgba() -> anyhow::Result<image::RgbaImage> {
+    Ok(image::load_from_memory_with_format(ICON, image::ImageFormat::Png)?.into_rgba8())
+}
+
+/// The tray on Windows and macOS, which lives in the event loop of the main thread.
+mod platform {
+    use tray_icon::{
+        Icon, TrayIcon, TrayIconBuilder,
+        menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
+    };
+
+    use super::TrayEvent;
+    use crate::canvas_tool::Tool;
+
+    pub struct Tray(TrayIcon);
+
+    impl Tray {
+        pub fn new(tx: async_channel::Sender<TrayEvent>) -> anyhow::Result<Self> {
+            let rgba = super::icon_rgba()?;
+            let (width, height) = rgba.dimensions();
+            let icon = Icon::from_rgba(rgba.into_raw(), width, height)?;
+
+            // The menu items are identified by the index of their event.
+            let mut events = Vec::new();
+            let mut item = |label: &str, event: TrayEvent| {
+                events.push(event);
+                MenuItem::with_id((events.len() - 1).to_string(), label, true, None)
+            };
+
+            let tools: Vec<_> = Tool::ALL
+                .iter()
+                .map(|&tool| item(tool.canvas_tool().label(), TrayEvent::SetTool(tool)))
+                .collect();
+            let tool_refs: Vec<_> = tools
+                .iter()
+                .map(|tool| tool as &dyn tray_icon::menu::IsMenuItem)
+                .collect();
+
+            let show = item("Show Controller", TrayEvent::ShowController);
+            let hide = item("Hide Controller", TrayEvent::HideController);
+            let clear = item("Clear All Canvases", TrayEvent::Clear);
+            let quit = item("Quit Monica", TrayEvent::Quit);
+
+            let menu = Menu::new();
+            menu.append_items(&[
+                &show,
+                &hide,
+                &PredefinedMenuItem::separator(),
+                &Submenu::with_items("Tool", true, &tool_refs)?,
+                &clear,
+                &PredefinedMenuItem::separator(),
+                &quit,
+            ])?;
+
+            MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+                let event = event
+                    .id()
+                    .0
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| events.get(index));
+
+                if let Some(event) = event {
+                    _ = tx.send_blocking(*event);
+                }
+            }));
+
+            let tray = TrayIconBuilder::new()
+                .with_menu(Box::new(menu))
+                .with_tooltip("Monica")
+                .with_icon(icon)
+                .build()?;
+
+            Ok(Self(tray))
+        }
+    }
+}
@@ -1,17 +1,25 @@
 use display_config::DisplayId;
 use gpui::{
-    AnyWindowHandle, App, Entity, MouseMoveEvent, Pixels, Point, ReadGlobal, UpdateGlobal, canvas,
-    div, prelude::*,
+    AnyWindowHandle, App, Entity, MouseButton, MouseMoveEvent, Pixels, Point, ReadGlobal,
+    ScrollWheelEvent, UpdateGlobal, Window, canvas, div, prelude::*, px,
 };
 
 use crate::{
-    canvas::{Tool, ToolState},
+    canvas::ToolState,
     canvas_orchestrator::CanvasOrchestrator,
+    canvas_tool::{PointerEvent, ScrollEvent, Tool},
+    keystroke::KeystrokeDisplay,
+    settings::{Corner, Settings},
 };
 
+/// The distance which a notch of the mouse wheel scrolls.
+pub const WHEEL_LINE_HEIGHT: Pixels = px(20.);
+
 pub struct CanvasView {
     _window_handle: AnyWindowHandle,
     display_id: DisplayId,
+    /// Whether the frame is shown to tell where the limited region is.
+    framed: bool,
 }
 
 impl CanvasView {
@@ -28,9 +36,15 @@ impl CanvasView {
             }
         });
 
-        let view = cx.new(|_| Self {
-            _window_handle: window_handle,
-            display_id,
+        let view = cx.new(|cx| {
+            cx.observe_global::<KeystrokeDisplay>(|_, cx| cx.notify())
+                .detach();
+
+            Self {
+                _window_handle: window_handle,
+                display_id,
+                framed: false,
+            }
         });
         cx.observe_release(&view, |view, cx| {
             CanvasOrchestrator::update_global(cx, |orchestrator, _| {
@@ -42,6 +56,79 @@ impl CanvasView {
         view
     }
 
+    pub fn set_framed(&mut self, framed: bool) {
+        self.framed = framed;
+    }
+
+    /// Render the bubble of the keystrokes if they are shown on this display.
+    fn render_keystroke_bubble(&self, window: &mut Window, cx: &App) -> Option<impl IntoElement> {
+        if !KeystrokeDisplay::is_shown_on(cx, &self.display_id) {
+            return None;
+        }
+
+        let keystroke = KeystrokeDisplay::global(cx);
+        let text = keystroke.text()?;
+
+        // Keep rendering until the bubble fades out.
+        window.request_animation_frame();
+
+        let bubble = div()
+            .absolute()
+            .px_4()
+            .py_2()
+            .rounded_lg()
+            .bg(gpui::black().alpha(0.7))
+            .text_color(gpui::white())
+            .text_2xl()
+            .opacity(keystroke.opacity())
+            .child(text);
+
+        Some(match Settings::global(cx).keystroke.corner {
+            Corner::TopLeft => bubble.top_8().left_8(),
+            Corner::TopRight => bubble.top_8().right_8(),
+            Corner::BottomLeft => bubble.bottom_8().left_8(),
+            Corner::BottomRight => bubble.bottom_8().right_8(),
+        })
+    }
+
+    fn on_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let delta = event.delta.pixel_delta(WHEEL_LINE_HEIGHT);
+        // Some platforms turn the vertical scroll with shift into the horizontal one.
+        let amount = f32::from(delta.y + delta.x);
+        if amount == 0. {
+            return;
+        }
+
+        self.scroll(
+            cx,
+            ScrollEvent {
+                position: event.position,
+                amount,
+                modifiers: event.modifiers,
+            },
+        );
+    }
+
+    /// Let the tool handle the mouse wheel, e.g. to adjust the spotlight.
+    pub fn scroll(&mut self, cx: &mut Context<Self>, event: ScrollEvent) {
+        let tool = ToolState::global(cx).tool().canvas_tool();
+
+        let display_id = self.display_id.clone();
+        CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+            orchestrator.action_canvas(cx, display_id, |canvas, cx| {
+                let actions = tool.on_scroll(canvas, cx, &event);
+                cx.notify();
+
+                actions
+            });
+        });
+    }
+
     pub fn on_mouse_move_whenever_window_inactive(
         &self,
         cx: &mut App,
@@ -50,21 +137,19 @@ impl CanvasView {
     ) {
         orchestrator.notify_old_working_canvas(cx, Some(&self.display_id));
 
-        if ToolState::global(cx).tool() == Tool::Highlight {
-            orchestrator.update_canvas(cx, &self.display_id, |canvas, cx| {
-                #[cfg(target_os = "windows")]
-                {
-                    let mut mouse_pos = mouse_pos;
-
-                    // Without offset, the cursor highlight get a little off.
-                    const CURSOR_OFFSET_X: f32 = 5.;
-                    const CURSOR_OFFSET_Y: f32 = 5.;
-
-                    mouse_pos.x += gpui::px(CURSOR_OFFSET_X);
-                    mouse_pos.y += gpui::px(CURSOR_OFFSET_Y);
-                }
+        let tool = ToolState::global(cx).tool();
+        if tool.canvas_tool().follows_cursor() {
+            // The offset corrects where the highlight is drawn around the cursor,
+            // while the other tools are centered at the cursor.
+            let highlight = &Settings::global(cx).highlight;
+            let offset = if tool == Tool::Highlight {
+                gpui::point(gpui::px(highlight.offset_x), gpui::px(highlight.offset_y))
+            } else {
+                Point::default()
+            };
 
-                canvas.set_highlight(mouse_pos);
+            orchestrator.update_canvas(cx, &self.display_id, |canvas, cx| {
+                canvas.set_highlight(mouse_pos + offset);
                 cx.notify();
             });
         }
@@ -72,66 +157,140 @@ impl CanvasView {
 }
 
 impl Render for CanvasView {
-    fn render(&mut self, _window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
         let display_id = self.display_id.clone();
+        let keystroke_bubble = self.render_keystroke_bubble(window, cx);
 
         div()
             .size_full()
             .bg(gpui::transparent_white())
+            .when(self.framed, |this| {
+                this.border_2().border_color(gpui::blue().alpha(0.6))
+            })
             .child(
                 canvas(|_bounds, _window, _cx| {}, {
                     let display_id = display_id.clone();
 
                     move |_, _, window, cx| {
+                        let tool = ToolState::global(cx).tool().canvas_tool();
+
                         CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
-                            orchestrator
-                                .update_canvas(cx, &display_id, |canvas, _| canvas.paint(window));
+                            orchestrator.update_canvas(cx, &display_id, |canvas, cx| {
+                                canvas.paint(window, tool, Settings::global(cx));
+
+                                // Keep repainting until the animation such as click ripples ends.
+                                if canvas.is_animating() {
+                                    window.request_animation_frame();
+                                }
+                            });
                         });
                     }
                 })
                 .bg(gpui::transparent_white()),
             )
+            .children(keystroke_bubble)
+            .on_scroll_wheel(cx.listener(Self::on_scroll_wheel))
+            .on_mouse_down(MouseButton::Left, {
+                let display_id = display_id.clone();
+
+                move |event, _, cx| {
+                    let tool = ToolState::global(cx).tool().canvas_tool();
+                    let event = PointerEvent {
+                        position: event.position,
+                        button: MouseButton::Left,
+                        modifiers: event.modifiers,
+                    };
+
+                    CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+                        orchestrator.action_canvas(cx, display_id.clone(), |canvas, cx| {
+                            let actions = tool.on_pointer_down(canvas, cx, &event);
+                            if actions > 0 {
+                                bring_main_window_foreground(cx);
+                                cx.notify();
+                            }
+
+                            actions
+                        });
+                    });
+                }
+            })
+            .on_mouse_up(MouseButton::Left, {
+                let display_id = display_id.clone();
+
+                move |_, _, cx| {
+                    let tool = ToolState::global(cx).tool().canvas_tool();
+
+                    CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+                        orchestrator.action_canvas(cx, display_id.clone(), |canvas, cx| {
+                            let actions = tool.on_pointer_up(canvas, cx);
+                            if actions > 0 {
+                                bring_main_window_foreground(cx);
+                                cx.notify();
+                            }
+
+                            actions
+                        });
+                    });
+                }
+            })
             .on_mouse_move(cx.listener(move |_view, event: &MouseMoveEvent, _, cx| {
                 let display_id = display_id.clone();
 
                 CanvasOrchestrator::update_global(cx, move |orchestrator, cx| {
+                    // Windows dispatches it manually, see `CanvasWindowManager`.
                     #[cfg(not(target_os = "windows"))]
                     _view.on_mouse_move_whenever_window_inactive(cx, orchestrator, event.position);
 
-                    // Canvas draw tool
-                    if matches!(event.pressed_button, Some(gpui::MouseButton::Left)) {
+                    let tool = ToolState::global(cx).tool().canvas_tool();
+                    let button = event
+                        .pressed_button
+                        .filter(|button| matches!(button, MouseButton::Left | MouseButton::Right));
+
+                    let mut handled = false;
+                    if let Some(button) = button {
+                        let event = PointerEvent {
+                            position: event.position,
+                            button,
+                            modifiers: event.modifiers,
+                        };
+
                         orchestrator.update_canvas(cx, &display_id, |canvas, cx| {
-                            canvas.draw(cx, event.position);
+                            handled = tool.on_pointer_drag(canvas, cx, &event);
                             cx.notify();
                         });
-                    } else {
+                    }
+
+                    if !handled {
+                        let recognize_shapes = ToolState::global(cx).recognize_shapes;
+
                         orchestrator.action_canvas(cx, display_id, |canvas, cx| {
                             let result = if canvas.is_painting() {
-                                canvas.flush();
-
-                                // On windows, the canvas window comes to the front over the main window.
-                                // This prevents interaction with the main window,
-                                // so we implement processing to bring the main window back to the front.
-                                #[cfg(target_os = "windows")]
-                                {
-                                    use crate::main_window::MainWindow;
-
-                                    MainWindow::update_global(cx, |window, cx| {
-                                        window.bring_foreground(cx)
-                                    });
-                                }
+                                let actions = canvas.flush(recognize_shapes);
+                                bring_main_window_foreground(cx);
 
-                                true
+                                actions
                             } else {
-                                false
+                                0
                             };
 
                             cx.notify();
 
                             result
                         });
-                    };
+                    }
                 });
             }))
     }
 }
+
+/// On windows, the canvas window comes to the front over the main window.
+/// This prevents interaction with the main window,
+/// so we implement processing to bring the main window back to the front.
+fn bring_main_window_foreground(_cx: &mut App) {
+    #[cfg(target_os = "windows")]
+    {
+        use crate::main_window::MainWindow;
+
+        MainWindow::update_global(_cx, |window, cx| window.bring_foreground(cx));
+    }
+}
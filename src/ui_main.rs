@@ -1,50 +1,338 @@
+use std::time::{Duration, Instant};
+
 use gpui::{
-    App, ElementId, Entity, MouseButton, ReadGlobal, UpdateGlobal, WindowControlArea, div,
-    prelude::*, px,
+    App, ElementId, Entity, EventEmitter, MouseButton, ReadGlobal, Task, UpdateGlobal, Window,
+    WindowControlArea, div, prelude::*, px, size,
 };
 use gpui_component::{
     ActiveTheme, Icon, Selectable, Sizable,
     button::{Button, ButtonCustomVariant, ButtonGroup, ButtonVariants},
+    checkbox::Checkbox,
     h_flex, v_flex,
 };
 
 use crate::{
-    canvas::{Tool, ToolState},
-    canvas_orchestrator::CanvasOrchestrator,
+    canvas::ToolState,
+    canvas_orchestrator::{ActionScope, CanvasOrchestrator, HistoryEntry, OrchestratorEvent},
+    canvas_tool::Tool,
+    canvas_window_manager::CanvasWindowManager,
+    capture::{self, CaptureTarget},
+    main_window::MainWindow,
+    platform_impl::{self, TargetWindow},
+    settings::{Settings, ToolbarLayout},
+    settings_window::SettingsWindow,
 };
 
+/// The panel shown below the tools.
+#[derive(Clone)]
+enum Panel {
+    Displays(Entity<DisplayList>),
+    Windows(Entity<WindowList>),
+    History(Entity<HistoryList>),
+}
+
+impl Panel {
+    fn height(&self, cx: &App) -> gpui::Pixels {
+        match self {
+            Self::Displays(_) => {
+                let display_count = CanvasWindowManager::global(cx).displays().len();
+                DisplayList::height(display_count)
+            }
+            Self::Windows(list) => list.read(cx).height(),
+            Self::History(_) => HistoryList::height(cx),
+        }
+    }
+}
+
+impl IntoElement for Panel {
+    type Element = gpui::AnyElement;
+
+    fn into_element(self) -> Self::Element {
+        match self {
+            Self::Displays(list) => list.into_any_element(),
+            Self::Windows(list) => list.into_any_element(),
+            Self::History(list) => list.into_any_element(),
+        }
+    }
+}
+
+/// The width of the column with the options and the panels in the vertical layout.
+const SIDE_COLUMN_WIDTH: f32 = 260.;
+
 pub struct AppView {
     title_bar: Entity<TitleBar>,
     tool_select: Entity<ToolSelect>,
+    panel: Option<Panel>,
+    /// The layout which the window size is fitted to, and whether it is collapsed.
+    layout: (ToolbarLayout, bool),
+    /// Snapping the window to the edge after it stops moving.
+    snap_task: Option<Task<()>>,
 }
 
 impl AppView {
-    pub fn new(cx: &mut App) -> Entity<Self> {
-        cx.new(|cx| Self {
-            title_bar: cx.new(|_| TitleBar),
-            tool_select: cx.new(|_| ToolSelect),
+    pub fn new(window: &mut Window, cx: &mut App) -> Entity<Self> {
+        cx.new(|cx| {
+            let title_bar = cx.new(TitleBar::new);
+            cx.subscribe_in(&title_bar, window, Self::on_title_bar_event)
+                .detach();
+
+            // Show or hide the options of the tool, see `CanvasTool::render_options`.
+            cx.observe_global_in::<ToolState>(window, |this, window, cx| {
+                this.fit_window_size(window, cx);
+                cx.notify();
+            })
+            .detach();
+
+            cx.observe_window_bounds(window, |this, window, cx| {
+                MainWindow::save_position(cx, window.bounds());
+
+                // The window keeps moving while it is dragged, so wait until it stops.
+                this.snap_task = Some(cx.spawn_in(window, async move |_, cx| {
+                    cx.background_executor()
+                        .timer(Duration::from_millis(500))
+                        .await;
+
+                    _ = cx.update(|window, cx| MainWindow::snap_to_edge(window, cx));
+                }));
+            })
+            .detach();
+
+            // Fit the window to the layout chosen in the settings window.
+            cx.observe_global_in::<Settings>(window, |this, window, cx| {
+                let layout = Self::layout(cx);
+
+                if this.layout != layout {
+                    this.layout = layout;
+                    this.fit_window_size(window, cx);
+                    cx.notify();
+                }
+            })
+            .detach();
+
+            // Fit the window to the display list when the displays are changed.
+            cx.observe_global_in::<CanvasWindowManager>(window, |this, window, cx| {
+                if matches!(this.panel, Some(Panel::Displays(_))) {
+                    this.fit_window_size(window, cx);
+                }
+            })
+            .detach();
+
+            // Fit the window to the history list when an action is added.
+            let events = CanvasOrchestrator::global(cx).events().clone();
+            cx.subscribe_in(&events, window, |this, _, event, window, cx| {
+                if *event == OrchestratorEvent::HistoryChanged
+                    && matches!(this.panel, Some(Panel::History(_)))
+                {
+                    this.fit_window_size(window, cx);
+                }
+            })
+            .detach();
+
+            Self {
+                title_bar,
+                tool_select: cx.new(ToolSelect::new),
+                panel: None,
+                layout: Self::layout(cx),
+                snap_task: None,
+            }
         })
     }
-}
 
-impl Render for AppView {
-    fn render(&mut self, _window: &mut gpui::Window, _cx: &mut Context<Self>) -> impl IntoElement {
-        v_flex()
-            .id("main-window-view")
+    fn layout(cx: &App) -> (ToolbarLayout, bool) {
+        let toolbar = &Settings::global(cx).toolbar;
+        (toolbar.layout, toolbar.collapsed)
+    }
+
+    fn on_title_bar_event(
+        &mut self,
+        _title_bar: &Entity<TitleBar>,
+        event: &TitleBarEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let TitleBarEvent::TogglePanel(kind) = event;
+
+        self.panel = match (kind, self.panel.take()) {
+            (PanelKind::Displays, Some(Panel::Displays(_)))
+            | (PanelKind::Windows, Some(Panel::Windows(_)))
+            | (PanelKind::History, Some(Panel::History(_))) => None,
+            (PanelKind::Displays, _) => Some(Panel::Displays(cx.new(DisplayList::new))),
+            (PanelKind::Windows, _) => Some(Panel::Windows(cx.new(WindowList::new))),
+            (PanelKind::History, _) => Some(Panel::History(cx.new(HistoryList::new))),
+        };
+
+        self.fit_window_size(window, cx);
+        cx.notify();
+    }
+
+    fn fit_window_size(&self, window: &mut Window, cx: &App) {
+        let base_size = MainWindow::base_size(cx);
+        if self.layout.1 {
+            window.resize(base_size);
+            return;
+        }
+
+        let option_height = ToolState::global(cx).tool().canvas_tool().options_height();
+        let panel_height = self.panel.as_ref().map_or(px(0.), |panel| panel.height(cx));
+
+        match self.layout.0 {
+            ToolbarLayout::Horizontal => {
+                window.resize(size(
+                    base_size.width,
+                    base_size.height + option_height + panel_height,
+                ));
+            }
+            // The options and the panel are put in the column beside the buttons.
+            ToolbarLayout::Vertical => {
+                let side_height = option_height + panel_height;
+                let width = if side_height > px(0.) {
+                    base_size.width + px(SIDE_COLUMN_WIDTH)
+                } else {
+                    base_size.width
+                };
+
+                window.resize(size(width, base_size.height.max(side_height)));
+            }
+        }
+    }
+
+    /// Show only the chosen tool with the button to expand the window.
+    fn render_collapsed(&self, cx: &App) -> impl IntoElement {
+        let vertical = self.layout.0 == ToolbarLayout::Vertical;
+        let tool = ToolState::global(cx).tool();
+
+        if vertical { v_flex() } else { h_flex() }
             .size_full()
-            .child(self.title_bar.clone())
-            .child(self.tool_select.clone())
-            .on_mouse_move(|_, _, cx| {
-                CanvasOrchestrator::update_global(cx, move |orchestrator, cx| {
-                    orchestrator.notify_old_working_canvas(cx, None);
-                });
+            .items_center()
+            .justify_center()
+            .gap_2()
+            // Leave the space for the traffic lights.
+            .when(cfg!(target_os = "macos"), |this| {
+                if vertical {
+                    this.pt_8()
+                } else {
+                    this.pl(px(72.))
+                }
+            })
+            .bg(cx.theme().title_bar)
+            .child(Icon::empty().path(tool.canvas_tool().icon()).large())
+            .child(
+                Button::new("expand-button")
+                    .icon(Icon::empty().path("icons/maximize-2.svg"))
+                    .ghost()
+                    .small()
+                    .tooltip("Show all tools")
+                    .on_click(|_, _, cx| {
+                        Settings::update(cx, |settings| settings.toolbar.collapsed = false);
+                    }),
+            )
+            .window_control_area(WindowControlArea::Drag)
+            .on_mouse_down(MouseButton::Left, |_event, window, _cx| {
+                window.start_window_move();
             })
     }
 }
 
-struct TitleBar;
+impl Render for AppView {
+    fn render(&mut self, _window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let tool = ToolState::global(cx).tool();
+
+        let options = tool.canvas_tool().render_options(cx);
+
+        let view = match self.layout {
+            (_, true) => div().size_full().child(self.render_collapsed(cx)),
+            (ToolbarLayout::Horizontal, false) => v_flex()
+                .size_full()
+                .child(self.title_bar.clone())
+                .child(self.tool_select.clone())
+                .children(options)
+                .children(self.panel.clone()),
+            (ToolbarLayout::Vertical, false) => {
+                let has_side_column = options.is_some() || self.panel.is_some();
+
+                h_flex()
+                    .size_full()
+                    .items_start()
+                    .child(self.title_bar.clone())
+                    .child(self.tool_select.clone())
+                    .when(has_side_column, |this| {
+                        this.child(
+                            v_flex()
+                                .w(px(SIDE_COLUMN_WIDTH))
+                                .h_full()
+                                .border_l_1()
+                                .border_color(cx.theme().border)
+                                .children(options)
+                                .children(self.panel.clone()),
+                        )
+                    })
+            }
+        };
+
+        view.id("main-window-view").on_mouse_move(|_, _, cx| {
+            CanvasOrchestrator::update_global(cx, move |orchestrator, cx| {
+                orchestrator.notify_old_working_canvas(cx, None);
+            });
+        })
+    }
+}
+
+/// The width of the title bar in the vertical layout.
+const TITLE_BAR_COLUMN_WIDTH: f32 = 44.;
+
+/// The panel which the title bar button opens or closes.
+enum PanelKind {
+    Displays,
+    Windows,
+    History,
+}
+
+enum TitleBarEvent {
+    TogglePanel(PanelKind),
+}
+
+struct TitleBar {
+    /// Whether a region is being selected and whether any canvas is pinned to a window.
+    window_state: (bool, bool),
+}
+
+impl EventEmitter<TitleBarEvent> for TitleBar {}
 
 impl TitleBar {
+    fn new(cx: &mut Context<Self>) -> Self {
+        // The annotations may be hidden with the shortcut keys or the tray.
+        let events = CanvasOrchestrator::global(cx).events().clone();
+        cx.subscribe(&events, |_, _, event, cx| {
+            if *event == OrchestratorEvent::AnnotationsHiddenChanged {
+                cx.notify();
+            }
+        })
+        .detach();
+
+        // The window manager is also updated on every cursor move on Windows,
+        // so repaint only when the state on the buttons changes.
+        cx.observe_global::<CanvasWindowManager>(|this, cx| {
+            let window_state = Self::window_state(cx);
+            if this.window_state != window_state {
+                this.window_state = window_state;
+                cx.notify();
+            }
+        })
+        .detach();
+
+        Self {
+            window_state: Self::window_state(cx),
+        }
+    }
+
+    fn window_state(cx: &App) -> (bool, bool) {
+        let manager = CanvasWindowManager::global(cx);
+        (
+            manager.is_selecting_region(),
+            !manager.pinned_window_ids().is_empty(),
+        )
+    }
+
     fn render_normal_button(
         &self,
         cx: &mut App,
@@ -62,22 +350,36 @@ impl TitleBar {
 
 impl Render for TitleBar {
     fn render(&mut self, _window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
-        h_flex()
-            .w_full()
-            .h_10()
+        let annotations_hidden = CanvasOrchestrator::global(cx).are_annotations_hidden();
+        let (selecting_region, pinned) = self.window_state;
+        let vertical = Settings::global(cx).toolbar.layout == ToolbarLayout::Vertical;
+
+        if vertical { v_flex() } else { h_flex() }
+            .map(|this| {
+                if vertical {
+                    // Leave the space for the traffic lights above the buttons.
+                    this.h_full()
+                        .w(px(TITLE_BAR_COLUMN_WIDTH))
+                        .when(cfg!(target_os = "macos"), |this| this.pt_8())
+                        .border_r_1()
+                } else {
+                    this.w_full().h_10().border_b_1()
+                }
+            })
             .items_center()
             .bg(cx.theme().title_bar)
-            .border_b_1()
             .border_color(cx.theme().border)
             .py_1()
             .px_1()
             .child(
-                h_flex()
-                    .when_else(
-                        cfg!(target_os = "macos"),
-                        |this| this.ml_auto(),
-                        |this| this.mr_auto(),
-                    )
+                if vertical { v_flex() } else { h_flex() }
+                    .when(!vertical, |this| {
+                        this.when_else(
+                            cfg!(target_os = "macos"),
+                            |this| this.ml_auto(),
+                            |this| this.mr_auto(),
+                        )
+                    })
                     .items_center()
                     .px_1()
                     .gap_1()
@@ -89,6 +391,13 @@ impl Render for TitleBar {
                                 });
                             })),
                     )
+                    .child(
+                        self.render_normal_button(cx, "history-button", "icons/history.svg")
+                            .tooltip("Jump back to a recent action")
+                            .on_click(cx.listener(|_, _, _, cx| {
+                                cx.emit(TitleBarEvent::TogglePanel(PanelKind::History));
+                            })),
+                    )
                     .child(
                         self.render_normal_button(cx, "trash-button", "icons/trash-2.svg")
                             .custom(
@@ -101,17 +410,90 @@ impl Render for TitleBar {
                                     orchestrator.clear(cx);
                                 });
                             })),
+                    )
+                    .child(
+                        self.render_normal_button(
+                            cx,
+                            "visibility-button",
+                            if annotations_hidden {
+                                "icons/eye-off.svg"
+                            } else {
+                                "icons/eye.svg"
+                            },
+                        )
+                        .selected(annotations_hidden)
+                        .tooltip("Hide or show the annotations")
+                        .on_click(cx.listener(|_, _, _, cx| {
+                            CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+                                orchestrator.toggle_annotations_hidden(cx);
+                            });
+                        })),
+                    )
+                    .child(
+                        self.render_normal_button(cx, "region-button", "icons/scan.svg")
+                            .selected(selecting_region)
+                            .on_click(cx.listener(|_, _, _, cx| {
+                                CanvasWindowManager::update_global(cx, |manager, cx| {
+                                    if manager.is_selecting_region() {
+                                        manager.cancel_region_selection(cx);
+                                    } else {
+                                        manager.start_region_selection(cx);
+                                    }
+                                });
+                            })),
+                    )
+                    .child(
+                        self.render_normal_button(cx, "pin-button", "icons/pin.svg")
+                            .selected(pinned)
+                            .on_click(cx.listener(|_, _, _, cx| {
+                                cx.emit(TitleBarEvent::TogglePanel(PanelKind::Windows));
+                            })),
+                    )
+                    .child(
+                        self.render_normal_button(cx, "displays-button", "icons/monitor.svg")
+                            .on_click(cx.listener(|_, _, _, cx| {
+                                cx.emit(TitleBarEvent::TogglePanel(PanelKind::Displays));
+                            })),
+                    )
+                    .child(
+                        self.render_normal_button(cx, "capture-button", "icons/camera.svg")
+                            .tooltip("Copy a capture of the display (Shift: save as PNG)")
+                            .on_click(|event, _, cx| {
+                                let target = if event.modifiers().shift {
+                                    CaptureTarget::File
+                                } else {
+                                    CaptureTarget::Clipboard
+                                };
+
+                                capture::capture_display(cx, target);
+                            }),
+                    )
+                    .child(
+                        self.render_normal_button(cx, "settings-button", "icons/settings.svg")
+                            .on_click(cx.listener(|_, _, _, cx| {
+                                SettingsWindow::update_global(cx, |window, cx| window.open(cx));
+                            })),
+                    )
+                    .child(
+                        self.render_normal_button(cx, "collapse-button", "icons/minimize-2.svg")
+                            .tooltip("Show only the chosen tool")
+                            .on_click(|_, _, cx| {
+                                Settings::update(cx, |settings| settings.toolbar.collapsed = true);
+                            }),
                     ),
             )
             .when(cfg!(target_os = "windows"), |this| {
                 this.child(
-                    h_flex().ml_auto().gap_1().child(
-                        div()
-                            .child(Icon::empty().path("icons/x.svg").large())
-                            .on_mouse_down(MouseButton::Left, |_, window, _| {
-                                window.remove_window()
-                            }),
-                    ),
+                    h_flex()
+                        .when_else(vertical, |this| this.mt_auto(), |this| this.ml_auto())
+                        .gap_1()
+                        .child(
+                            div()
+                                .child(Icon::empty().path("icons/x.svg").large())
+                                .on_mouse_down(MouseButton::Left, |_, window, _| {
+                                    window.remove_window()
+                                }),
+                        ),
                 )
             })
             .window_control_area(WindowControlArea::Drag)
@@ -121,27 +503,25 @@ impl Render for TitleBar {
     }
 }
 
-impl Tool {
-    fn from_number(number: usize) -> Self {
-        match number {
-            0 => Self::Cursor,
-            1 => Self::Pen,
-            2 => Self::Eraser,
-            3 => Self::Highlight,
-            _ => unreachable!(),
-        }
-    }
-}
+/// The width of the tool buttons in the vertical layout.
+const TOOL_COLUMN_WIDTH: f32 = 56.;
 
 struct ToolSelect;
 
 impl ToolSelect {
-    fn render_tool_button(
+    fn new(cx: &mut Context<Self>) -> Self {
+        // The tool may be changed without the buttons, e.g. by the eraser end of the stylus.
+        cx.observe_global::<ToolState>(|_, cx| cx.notify()).detach();
+
+        Self
+    }
+
+    fn render_toggle_button(
         &self,
         cx: &mut App,
         id: impl Into<ElementId>,
         icon_path: &'static str,
-        tool: Tool,
+        selected: bool,
     ) -> Button {
         Button::new(id)
             .icon(Icon::empty().path(icon_path))
@@ -149,43 +529,354 @@ impl ToolSelect {
             .custom(ButtonCustomVariant::new(cx).active(cx.theme().foreground.alpha(0.2)))
             .size_10()
             .with_size(px(36.))
-            .selected(tool == ToolState::global(cx).tool())
+            .selected(selected)
             .rounded_xl()
     }
+
+    fn render_tool_button(
+        &self,
+        cx: &mut App,
+        id: impl Into<ElementId>,
+        icon_path: &'static str,
+        tool: Tool,
+    ) -> Button {
+        let selected = tool == ToolState::global(cx).tool();
+        self.render_toggle_button(cx, id, icon_path, selected)
+    }
 }
 
 impl Render for ToolSelect {
     fn render(&mut self, _window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
-        h_flex().size_full().items_center().child(
-            ButtonGroup::new("toolbar-tools")
-                .size_full()
-                .justify_around()
-                .items_center()
-                .px_2()
-                .gap_2()
-                .child(self.render_tool_button(
-                    cx,
-                    "tool-cursor",
-                    "icons/mouse-pointer-2.svg",
-                    Tool::Cursor,
-                ))
-                .child(self.render_tool_button(cx, "tool-pen", "icons/pencil.svg", Tool::Pen))
-                .child(self.render_tool_button(cx, "tool-eraser", "icons/eraser.svg", Tool::Eraser))
-                .child(self.render_tool_button(
+        let recognize_shapes = ToolState::global(cx).recognize_shapes;
+        let vertical = Settings::global(cx).toolbar.layout == ToolbarLayout::Vertical;
+
+        let tools: Vec<_> = Tool::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, &tool)| {
+                self.render_tool_button(cx, ("tool", i), tool.canvas_tool().icon(), tool)
+            })
+            .collect();
+
+        if vertical { v_flex() } else { h_flex() }
+            .map(|this| {
+                if vertical {
+                    this.h_full().w(px(TOOL_COLUMN_WIDTH)).pb_2()
+                } else {
+                    this.w_full().h(px(60.)).pr_2()
+                }
+            })
+            .items_center()
+            .child(
+                ButtonGroup::new("toolbar-tools")
+                    .flex_1()
+                    .map(|this| {
+                        if vertical {
+                            this.flex_col().w_full().py_2()
+                        } else {
+                            this.h_full().px_2()
+                        }
+                    })
+                    .justify_around()
+                    .items_center()
+                    .gap_1()
+                    .children(tools)
+                    .on_click(cx.listener(|_, selected: &Vec<usize>, _, cx| {
+                        let Some(&tool) = selected.first().and_then(|i| Tool::ALL.get(*i)) else {
+                            return;
+                        };
+
+                        ToolState::update_global(cx, |state, cx| {
+                            state.set_tool(cx, tool);
+                        });
+
+                        cx.notify();
+                    })),
+            )
+            .child(
+                div()
+                    .when_else(vertical, |this| this.h_px().w_6(), |this| this.w_px().h_6())
+                    .bg(cx.theme().border),
+            )
+            .child(
+                self.render_toggle_button(
                     cx,
-                    "tool-highlight",
-                    "icons/circle.svg",
-                    Tool::Highlight,
-                ))
-                .on_click(cx.listener(|_, selected: &Vec<usize>, _, cx| {
-                    let tool = Tool::from_number(*selected.first().unwrap());
-
-                    ToolState::update_global(cx, |state, cx| {
-                        state.set_tool(cx, tool);
+                    "shape-recognition",
+                    "icons/shapes.svg",
+                    recognize_shapes,
+                )
+                .when_else(vertical, |this| this.mt_2(), |this| this.ml_2())
+                .tooltip("Turn the strokes into clean shapes")
+                .on_click(|_, _, cx| {
+                    ToolState::update_global(cx, |state, _| {
+                        state.recognize_shapes = !state.recognize_shapes;
                     });
+                }),
+            )
+    }
+}
 
-                    cx.notify();
-                })),
-        )
+/// The height of the row to choose the kind of the stamp or the sticker.
+const PANEL_ROW_HEIGHT: f32 = 28.;
+const PANEL_PADDING_Y: f32 = 8.;
+
+struct DisplayList;
+
+impl DisplayList {
+    fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<CanvasWindowManager>(|_, cx| cx.notify())
+            .detach();
+
+        Self
+    }
+
+    fn height(display_count: usize) -> gpui::Pixels {
+        px(PANEL_ROW_HEIGHT * display_count as f32 + PANEL_PADDING_Y * 2.)
+    }
+}
+
+impl Render for DisplayList {
+    fn render(&mut self, _window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = Settings::global(cx);
+
+        let rows = CanvasWindowManager::global(cx)
+            .displays()
+            .into_iter()
+            .enumerate()
+            .map(|(i, display)| {
+                let display_id = display.id.clone();
+                let label = format!(
+                    "{} ({}×{})",
+                    display.name, display.size.width, display.size.height
+                );
+
+                h_flex().h(px(PANEL_ROW_HEIGHT)).items_center().child(
+                    Checkbox::new(("display", i))
+                        .label(label)
+                        .checked(settings.is_display_enabled(&display_id))
+                        .on_click(move |checked, _, cx| {
+                            CanvasWindowManager::update_global(cx, |manager, cx| {
+                                manager.set_display_enabled(cx, &display_id, *checked);
+                            });
+                        }),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .w_full()
+            .py(px(PANEL_PADDING_Y))
+            .px_3()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .children(rows)
+    }
+}
+
+/// The list of application windows which a canvas can be pinned to.
+struct WindowList {
+    /// The windows listed when the list is opened.
+    windows: Vec<TargetWindow>,
+}
+
+impl WindowList {
+    const MAX_VISIBLE_ROWS: usize = 6;
+
+    fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<CanvasWindowManager>(|_, cx| cx.notify())
+            .detach();
+
+        Self {
+            windows: platform_impl::list_target_windows(),
+        }
+    }
+
+    fn height(&self) -> gpui::Pixels {
+        // The empty list shows a message in one row.
+        let rows = self.windows.len().clamp(1, Self::MAX_VISIBLE_ROWS);
+        px(PANEL_ROW_HEIGHT * rows as f32 + PANEL_PADDING_Y * 2.)
+    }
+}
+
+impl Render for WindowList {
+    fn render(&mut self, _window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let pinned_window_ids = CanvasWindowManager::global(cx).pinned_window_ids();
+
+        let rows = self
+            .windows
+            .iter()
+            .enumerate()
+            .map(|(i, target)| {
+                let pinned = pinned_window_ids.contains(&target.id);
+                let target = target.clone();
+
+                Button::new(("target-window", i))
+                    .label(target.title.clone())
+                    .ghost()
+                    .small()
+                    .w_full()
+                    .h(px(PANEL_ROW_HEIGHT))
+                    .selected(pinned)
+                    .on_click(move |_, _, cx| {
+                        CanvasWindowManager::update_global(cx, |manager, cx| {
+                            if pinned {
+                                manager.unpin_window(cx, target.id);
+                            } else {
+                                manager.pin_to_window(cx, &target);
+                            }
+                        });
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("window-list")
+            .w_full()
+            .h(self.height())
+            .py(px(PANEL_PADDING_Y))
+            .px_3()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .overflow_y_scroll()
+            .when(rows.is_empty(), |this| {
+                this.child(
+                    h_flex()
+                        .h(px(PANEL_ROW_HEIGHT))
+                        .items_center()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("No windows to pin the canvas to"),
+                )
+            })
+            .children(rows)
+    }
+}
+
+/// The recent actions, which the canvases can be brought back to.
+struct HistoryList {
+    /// Updating how long ago the actions were done.
+    _refresh_task: Task<()>,
+}
+
+impl HistoryList {
+    const MAX_VISIBLE_ROWS: usize = 8;
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+    fn new(cx: &mut Context<Self>) -> Self {
+        let events = CanvasOrchestrator::global(cx).events().clone();
+        cx.subscribe(&events, |_, _, event, cx| {
+            if *event == OrchestratorEvent::HistoryChanged {
+                cx.notify();
+            }
+        })
+        .detach();
+
+        let refresh_task = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(Self::REFRESH_INTERVAL).await;
+
+                if this.update(cx, |_, cx| cx.notify()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            _refresh_task: refresh_task,
+        }
+    }
+
+    fn height(cx: &App) -> gpui::Pixels {
+        let orchestrator = CanvasOrchestrator::global(cx);
+        let entries = orchestrator.history().count() + orchestrator.undone_history().count();
+
+        // The first row goes back to before the oldest action.
+        let rows = (entries + 1).min(Self::MAX_VISIBLE_ROWS);
+        px(PANEL_ROW_HEIGHT * rows as f32 + PANEL_PADDING_Y * 2.)
+    }
+
+    /// Describe the action, e.g. `Pen stroke on DELL U2720 · 2 min ago`.
+    fn describe(entry: &HistoryEntry, cx: &App) -> String {
+        let action = match &entry.scope {
+            ActionScope::Display(display_id) => {
+                let display = CanvasWindowManager::global(cx).display(display_id);
+                let name = display.map_or("a removed display", |display| &display.name);
+                format!("{} on {name}", entry.label)
+            }
+            ActionScope::All => entry.label.to_owned(),
+        };
+
+        format!("{action} · {}", Self::elapsed(entry.added_at))
+    }
+
+    fn elapsed(added_at: Instant) -> String {
+        let seconds = added_at.elapsed().as_secs();
+
+        match seconds {
+            0..5 => "just now".to_owned(),
+            5..60 => format!("{seconds} s ago"),
+            60..3600 => format!("{} min ago", seconds / 60),
+            _ => format!("{} h ago", seconds / 3600),
+        }
+    }
+}
+
+impl Render for HistoryList {
+    fn render(&mut self, _window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let orchestrator = CanvasOrchestrator::global(cx);
+        let done = orchestrator.history().count();
+
+        // The undone actions follow the done ones, so the list is the timeline from the oldest.
+        // Choosing an action brings the canvases back to just after it.
+        let rows = orchestrator
+            .history()
+            .chain(orchestrator.undone_history())
+            .enumerate()
+            .map(|(i, entry)| {
+                let count = i + 1;
+
+                Button::new(("history-entry", i))
+                    .label(Self::describe(entry, cx))
+                    .ghost()
+                    .small()
+                    .w_full()
+                    .h(px(PANEL_ROW_HEIGHT))
+                    .selected(count == done)
+                    .when(count > done, |this| {
+                        this.text_color(cx.theme().muted_foreground)
+                    })
+                    .on_click(move |_, _, cx| {
+                        CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+                            orchestrator.jump_to(cx, count);
+                        });
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("history-list")
+            .w_full()
+            .h(Self::height(cx))
+            .py(px(PANEL_PADDING_Y))
+            .px_3()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .overflow_y_scroll()
+            // The newest action is at the top.
+            .children(rows.into_iter().rev())
+            .child(
+                Button::new("history-start")
+                    .label("Before the recent actions")
+                    .ghost()
+                    .small()
+                    .w_full()
+                    .h(px(PANEL_ROW_HEIGHT))
+                    .selected(done == 0)
+                    .on_click(|_, _, cx| {
+                        CanvasOrchestrator::update_global(cx, |orchestrator, cx| {
+                            orchestrator.jump_to(cx, 0);
+                        });
+                    }),
+            )
     }
 }
@@ -0,0 +1,135 @@
+use display_config::DisplayId;
+use gpui::{
+    Bounds, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Point, UpdateGlobal,
+    Window, div, prelude::*, px,
+};
+
+use crate::canvas_window_manager::CanvasWindowManager;
+
+/// The region smaller than this is treated as a click.
+const MIN_REGION_SIZE: Pixels = px(8.);
+const HANDLE_SIZE: Pixels = px(12.);
+
+enum RegionDrag {
+    /// Drawing a new region from the point.
+    New(Point<Pixels>),
+    /// Moving the region grabbed at the offset from its origin.
+    Move(Point<Pixels>),
+    /// Resizing the region from its bottom-right corner.
+    Resize,
+}
+
+pub struct RegionSelectView {
+    display_id: DisplayId,
+    region: Option<Bounds<Pixels>>,
+    drag: Option<RegionDrag>,
+}
+
+impl RegionSelectView {
+    pub fn new(display_id: DisplayId, region: Option<Bounds<Pixels>>) -> Self {
+        Self {
+            display_id,
+            region,
+            drag: None,
+        }
+    }
+
+    fn handle_bounds(region: Bounds<Pixels>) -> Bounds<Pixels> {
+        let corner = region.bottom_right();
+
+        Bounds::from_corners(
+            Point::new(corner.x - HANDLE_SIZE, corner.y - HANDLE_SIZE),
+            corner,
+        )
+    }
+
+    fn on_mouse_down(&mut self, event: &MouseDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let pos = event.position;
+
+        self.drag = Some(match self.region {
+            Some(region) if Self::handle_bounds(region).contains(&pos) => RegionDrag::Resize,
+            Some(region) if region.contains(&pos) => RegionDrag::Move(pos - region.origin),
+            _ => {
+                self.region = None;
+                RegionDrag::New(pos)
+            }
+        });
+
+        cx.notify();
+    }
+
+    fn on_mouse_move(&mut self, event: &MouseMoveEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(drag) = self.drag.as_ref() else {
+            return;
+        };
+
+        let pos = event.position;
+        let region = match (drag, self.region) {
+            (RegionDrag::New(start), _) => Bounds::from_corners(start.min(&pos), start.max(&pos)),
+            (RegionDrag::Move(offset), Some(region)) => Bounds::new(pos - *offset, region.size),
+            (RegionDrag::Resize, Some(region)) => {
+                Bounds::from_corners(region.origin, pos.max(&region.origin))
+            }
+            _ => return,
+        };
+
+        self.region = Some(region);
+        cx.notify();
+    }
+
+    fn on_mouse_up(&mut self, _: &MouseUpEvent, _: &mut Window, cx: &mut Context<Self>) {
+        if self.drag.take().is_none() {
+            return;
+        }
+
+        // A click without dragging resets the canvas to the whole display.
+        let region = self.region.filter(|region| {
+            region.size.width >= MIN_REGION_SIZE && region.size.height >= MIN_REGION_SIZE
+        });
+        let display_id = self.display_id.clone();
+
+        // This window is closed by finishing the selection, so we do it after this event.
+        cx.defer(move |cx| {
+            CanvasWindowManager::update_global(cx, |manager, cx| {
+                manager.finish_region_selection(cx, &display_id, region);
+            });
+        });
+    }
+}
+
+impl Render for RegionSelectView {
+    fn render(&mut self, _window: &mut gpui::Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let frame = self.region.map(|region| {
+            let handle = Self::handle_bounds(region);
+
+            div()
+                .absolute()
+                .left(region.origin.x)
+                .top(region.origin.y)
+                .w(region.size.width)
+                .h(region.size.height)
+                .border_2()
+                .border_color(gpui::blue())
+                .bg(gpui::blue().alpha(0.1))
+                .cursor_move()
+                .child(
+                    div()
+                        .absolute()
+                        .left(handle.origin.x - region.origin.x)
+                        .top(handle.origin.y - region.origin.y)
+                        .size(HANDLE_SIZE)
+                        .bg(gpui::blue())
+                        .cursor_nwse_resize(),
+                )
+        });
+
+        div()
+            .size_full()
+            .bg(gpui::black().alpha(0.2))
+            .cursor_crosshair()
+            .children(frame)
+            .on_mouse_down(MouseButton::Left, cx.listener(Self::on_mouse_down))
+            .on_mouse_move(cx.listener(Self::on_mouse_move))
+            .on_mouse_up(MouseButton::Left, cx.listener(Self::on_mouse_up))
+    }
+}
@@ -0,0 +1,744 @@
+use gpui::{
+    App, FocusHandle, Hsla, IntoElement, KeyDownEvent, Keystroke, ReadGlobal, Rgba, SharedString,
+    UpdateGlobal, Window, canvas, div, prelude::*, px,
+};
+use gpui_component::{
+    ActiveTheme, Icon, Selectable, Sizable,
+    button::{Button, ButtonGroup, ButtonVariants},
+    checkbox::Checkbox,
+    h_flex, v_flex,
+};
+
+use crate::{
+    canvas::paint_highlight,
+    canvas_tool::Tool,
+    canvas_window_manager::CanvasWindowManager,
+    keystroke::KeystrokeDisplay,
+    platform_impl::{KeyStroke, Modifier},
+    settings::{Corner, HighlightSettings, HighlightStyle, Settings, ToolbarLayout},
+    shortcut::Shortcuts,
+    utils,
+};
+
+const ROW_HEIGHT: f32 = 28.;
+const COLORS: [fn() -> Hsla; 6] = [
+    gpui::red,
+    gpui::yellow,
+    gpui::green,
+    gpui::blue,
+    gpui::white,
+    gpui::black,
+];
+
+/// The mouse operations on the canvas, which can not be changed.
+const SHORTCUTS: [(&str, &str); 9] = [
+    ("Shift + drag", "Straight line"),
+    ("Ctrl + drag", "Rectangle"),
+    ("Alt + drag", "Ellipse"),
+    ("Right drag", "Erase"),
+    ("Wheel", "Sticker size"),
+    ("Alt + wheel", "Spotlight radius or zoom"),
+    ("Alt + Shift + wheel", "Spotlight opacity or lens radius"),
+    ("Alt + Ctrl + wheel", "Spotlight shape"),
+    ("Stylus eraser", "Erase"),
+];
+
+pub struct SettingsView {
+    focus_handle: FocusHandle,
+    /// Whether the keys pressed are taken as the shortcut to hide or show the annotations.
+    recording_shortcut: bool,
+}
+
+impl SettingsView {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<Settings>(|_, cx| cx.notify()).detach();
+        cx.observe_global::<CanvasWindowManager>(|_, cx| cx.notify())
+            .detach();
+        cx.observe_global::<Shortcuts>(|_, cx| cx.notify()).detach();
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            recording_shortcut: false,
+        }
+    }
+
+    /// The problems found in the settings file, with the button to dismiss them.
+    fn render_problems(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let problems = Settings::global(cx).problems();
+        if problems.is_empty() {
+            return None;
+        }
+
+        let rows = problems
+            .iter()
+            .map(|problem| div().text_xs().child(problem.clone()));
+
+        Some(
+            v_flex()
+                .gap_1()
+                .p_2()
+                .rounded_md()
+                .border_1()
+                .border_color(cx.theme().warning)
+                .child(
+                    h_flex()
+                        .items_center()
+                        .justify_between()
+                        .child(div().text_sm().child("Problems in the settings file"))
+                        .child(
+                            Button::new("dismiss-problems")
+                                .label("Dismiss")
+                                .ghost()
+                                .small()
+                                .on_click(|_, _, cx| Settings::dismiss_problems(cx)),
+                        ),
+                )
+                .children(rows),
+        )
+    }
+
+    fn render_tools_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = Settings::global(cx);
+        let tools = settings.tools.clone();
+        let spotlight = settings.spotlight.clone();
+        let magnifier = settings.magnifier.clone();
+
+        let default_tool = ButtonGroup::new("default-tool")
+            .children(Tool::ALL.iter().enumerate().map(|(i, &tool)| {
+                Button::new(("default-tool", i))
+                    .icon(Icon::empty().path(tool.canvas_tool().icon()))
+                    .small()
+                    .tooltip(tool.canvas_tool().label())
+                    .selected(tool == tools.default_tool)
+            }))
+            .on_click(|selected: &Vec<usize>, _, cx| {
+                if let Some(&tool) = selected.first().and_then(|i| Tool::ALL.get(*i)) {
+                    Settings::update(cx, |settings| settings.tools.default_tool = tool);
+                }
+            });
+
+        section("Tools")
+            .child(div().text_sm().child("Tool at startup"))
+            .child(default_tool)
+            .child(setting_row(
+                "Pen width",
+                stepper(
+                    "pen-width",
+                    format!("{}px", tools.pen_width),
+                    |settings, step| {
+                        settings
+                            .tools
+                            .set_pen_width(settings.tools.pen_width + step)
+                    },
+                ),
+            ))
+            .child(setting_row(
+                "Eraser radius",
+                stepper(
+                    "eraser-radius",
+                    format!("{}px", tools.eraser_radius),
+                    |settings, step| {
+                        let tools = &mut settings.tools;
+                        tools.set_eraser_radius(tools.eraser_radius + step * 2.);
+                    },
+                ),
+            ))
+            .child(setting_row(
+                "Spotlight radius",
+                stepper(
+                    "spotlight-radius",
+                    format!("{}px", spotlight.radius),
+                    |settings, step| {
+                        let spotlight = &mut settings.spotlight;
+                        spotlight.set_radius(spotlight.radius + step * 10.);
+                    },
+                ),
+            ))
+            .child(setting_row(
+                "Spotlight dimming",
+                stepper(
+                    "spotlight-opacity",
+                    format!("{:.0}%", spotlight.opacity * 100.),
+                    |settings, step| {
+                        let spotlight = &mut settings.spotlight;
+                        spotlight.set_opacity(spotlight.opacity + step * 0.05);
+                    },
+                ),
+            ))
+            .child(setting_row(
+                "Magnifier zoom",
+                stepper(
+                    "magnifier-zoom",
+                    format!("{:.1}x", magnifier.zoom),
+                    |settings, step| {
+                        let magnifier = &mut settings.magnifier;
+                        magnifier.set_zoom(magnifier.zoom + step * 0.5);
+                    },
+                ),
+            ))
+            .child(setting_row(
+                "Magnifier radius",
+                stepper(
+                    "magnifier-radius",
+                    format!("{}px", magnifier.radius),
+                    |settings, step| {
+                        let magnifier = &mut settings.magnifier;
+                        magnifier.set_radius(magnifier.radius + step * 10.);
+                    },
+                ),
+            ))
+    }
+
+    fn render_colors_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = Settings::global(cx);
+        let (pen, highlight) = (settings.tools.color, settings.highlight.color);
+        let (left_click, right_click) = (settings.ripple.left_color, settings.ripple.right_color);
+
+        section("Colors")
+            .child(setting_row(
+                "Pen at startup",
+                color_swatches("pen-color", pen, cx, |settings, color| {
+                    settings.tools.color = color;
+                }),
+            ))
+            .child(setting_row(
+                "Highlight",
+                color_swatches("highlight-color", highlight, cx, |settings, color| {
+                    settings.highlight.color = color;
+                }),
+            ))
+            .child(setting_row(
+                "Left click",
+                color_swatches("ripple-left-color", left_click, cx, |settings, color| {
+                    settings.ripple.left_color = color;
+                }),
+            ))
+            .child(setting_row(
+                "Right click",
+                color_swatches("ripple-right-color", right_click, cx, |settings, color| {
+                    settings.ripple.right_color = color;
+                }),
+            ))
+    }
+
+    fn render_highlight_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let highlight = Settings::global(cx).highlight.clone();
+
+        let preview = div()
+            .w_full()
+            .h(px(120.))
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().border)
+            .child(
+                canvas(|_, _, _| {}, {
+                    let highlight = highlight.clone();
+                    move |bounds, _, window, _| paint_highlight(window, bounds.center(), &highlight)
+                })
+                .size_full(),
+            );
+
+        let style = ButtonGroup::new("highlight-style")
+            .child(
+                Button::new("highlight-filled")
+                    .label("Filled")
+                    .small()
+                    .selected(highlight.style == HighlightStyle::Filled),
+            )
+            .child(
+                Button::new("highlight-ring")
+                    .label("Ring")
+                    .small()
+                    .selected(highlight.style == HighlightStyle::Ring),
+            )
+            .on_click(|selected: &Vec<usize>, _, cx| {
+                let style = match selected.first() {
+                    Some(0) => HighlightStyle::Filled,
+                    _ => HighlightStyle::Ring,
+                };
+
+                update_highlight(cx, |highlight| highlight.style = style);
+            });
+
+        section("Cursor highlight")
+            .child(preview)
+            .child(setting_row(
+                "Radius",
+                stepper(
+                    "highlight-radius",
+                    format!("{}px", highlight.radius),
+                    |settings, step| {
+                        let highlight = &mut settings.highlight;
+                        highlight.set_radius(highlight.radius + step * 2.);
+                    },
+                ),
+            ))
+            .child(setting_row(
+                "Opacity",
+                stepper(
+                    "highlight-opacity",
+                    format!("{:.0}%", highlight.opacity * 100.),
+                    |settings, step| {
+                        let highlight = &mut settings.highlight;
+                        highlight.set_opacity(highlight.opacity + step * 0.1);
+                    },
+                ),
+            ))
+            .child(setting_row("Style", style))
+            .child(setting_row(
+                "Offset X",
+                stepper(
+                    "highlight-offset-x",
+                    format!("{}px", highlight.offset_x),
+                    |settings, step| settings.highlight.offset_x += step,
+                ),
+            ))
+            .child(setting_row(
+                "Offset Y",
+                stepper(
+                    "highlight-offset-y",
+                    format!("{}px", highlight.offset_y),
+                    |settings, step| settings.highlight.offset_y += step,
+                ),
+            ))
+    }
+}
+
+impl SettingsView {
+    fn render_ripple_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let enabled = Settings::global(cx).ripple.enabled;
+
+        section("Click ripples").child(
+            Checkbox::new("ripple-enabled")
+                .label("Show a ripple at every click")
+                .checked(enabled)
+                .on_click(|checked, _, cx| {
+                    CanvasWindowManager::update_global(cx, |manager, cx| {
+                        manager.set_click_ripples_enabled(cx, *checked);
+                    });
+                }),
+        )
+    }
+}
+
+impl SettingsView {
+    fn render_keystroke_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        const CORNERS: [(Corner, &str); 4] = [
+            (Corner::TopLeft, "Top left"),
+            (Corner::TopRight, "Top right"),
+            (Corner::BottomLeft, "Bottom left"),
+            (Corner::BottomRight, "Bottom right"),
+        ];
+
+        let keystroke = Settings::global(cx).keystroke.clone();
+
+        // The first choice is the primary display.
+        let display_keys: Vec<_> = CanvasWindowManager::global(cx)
+            .displays()
+            .into_iter()
+            .map(|display| (Some(utils::display_key(&display.id)), display.name.clone()))
+            .collect();
+        let display_keys: Vec<_> = std::iter::once((None, "Primary".to_owned()))
+            .chain(display_keys)
+            .collect();
+
+        let displays = ButtonGroup::new("keystroke-display")
+            .children(display_keys.iter().enumerate().map(|(i, (key, name))| {
+                Button::new(("keystroke-display", i))
+                    .label(name.clone())
+                    .small()
+                    .selected(*key == keystroke.display)
+            }))
+            .on_click(move |selected: &Vec<usize>, _, cx| {
+                if let Some((key, _)) = selected.first().and_then(|i| display_keys.get(*i)) {
+                    let key = key.clone();
+                    Settings::update(cx, |settings| settings.keystroke.display = key);
+                }
+            });
+
+        let corners = ButtonGroup::new("keystroke-corner")
+            .children(CORNERS.iter().map(|(corner, label)| {
+                Button::new(*label)
+                    .label(*label)
+                    .small()
+                    .selected(*corner == keystroke.corner)
+            }))
+            .on_click(|selected: &Vec<usize>, _, cx| {
+                if let Some((corner, _)) = selected.first().and_then(|i| CORNERS.get(*i)) {
+                    Settings::update(cx, |settings| settings.keystroke.corner = *corner);
+                }
+            });
+
+        section("Keystrokes")
+            .child(
+                Checkbox::new("keystroke-enabled")
+                    .label("Show the pressed keys")
+                    .checked(keystroke.enabled)
+                    .on_click(|checked, _, cx| {
+                        KeystrokeDisplay::update_global(cx, |display, cx| {
+                            display.set_enabled(cx, *checked);
+                        });
+                    }),
+            )
+            .child(div().text_sm().child("Display"))
+            .child(displays)
+            .child(div().text_sm().child("Corner"))
+            .child(corners)
+    }
+}
+
+impl SettingsView {
+    fn render_displays_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = Settings::global(cx);
+
+        let rows = CanvasWindowManager::global(cx)
+            .displays()
+            .into_iter()
+            .enumerate()
+            .map(|(i, display)| {
+                let display_id = display.id.clone();
+
+                Checkbox::new(("settings-display", i))
+                    .label(format!("Cover {}", display.name))
+                    .checked(settings.is_display_enabled(&display_id))
+                    .on_click(move |checked, _, cx| {
+                        CanvasWindowManager::update_global(cx, |manager, cx| {
+                            manager.set_display_enabled(cx, &display_id, *checked);
+                        });
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        section("Displays").children(rows)
+    }
+
+    fn render_shortcuts_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let keys = if self.recording_shortcut {
+            "Press the keys…".to_owned()
+        } else {
+            Settings::global(cx)
+                .shortcuts
+                .toggle_annotations
+                .clone()
+                .unwrap_or_else(|| "None".to_owned())
+        };
+
+        let keys_field = h_flex()
+            .id("toggle-annotations-keys")
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::on_shortcut_key_down))
+            .gap_1()
+            .child(
+                Button::new("record-toggle-annotations")
+                    .label(keys)
+                    .small()
+                    .selected(self.recording_shortcut)
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.recording_shortcut = true;
+                        window.focus(&this.focus_handle);
+                        Shortcuts::suspend(cx);
+                        cx.notify();
+                    })),
+            )
+            .child(
+                Button::new("clear-toggle-annotations")
+                    .label("Clear")
+                    .ghost()
+                    .small()
+                    .on_click(|_, _, cx| {
+                        Settings::update(cx, |settings| {
+                            settings.shortcuts.toggle_annotations = None;
+                        });
+                    }),
+            );
+
+        section("Shortcuts")
+            .child(setting_row("Hide or show annotations", keys_field))
+            .children(Shortcuts::global(cx).error().map(|error| {
+                div()
+                    .text_xs()
+                    .text_color(cx.theme().warning)
+                    .child(error.to_owned())
+            }))
+            .children(SHORTCUTS.iter().map(|(keys, action)| {
+                h_flex()
+                    .w_full()
+                    .items_center()
+                    .justify_between()
+                    .text_sm()
+                    .child(div().font_weight(gpui::FontWeight::MEDIUM).child(*keys))
+                    .child(*action)
+            }))
+    }
+
+    /// Set the keys pressed while the shortcut field is recording, or stop with Escape.
+    fn on_shortcut_key_down(
+        &mut self,
+        event: &KeyDownEvent,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.recording_shortcut {
+            return;
+        }
+        cx.stop_propagation();
+
+        if event.keystroke.key == "escape" {
+            self.recording_shortcut = false;
+            Shortcuts::update(cx);
+            cx.notify();
+            return;
+        }
+
+        // Keep recording until a key is pressed with a modifier.
+        let Some(keys) = shortcut_keys(&event.keystroke) else {
+            return;
+        };
+
+        self.recording_shortcut = false;
+        Settings::update(cx, |settings| {
+            settings.shortcuts.toggle_annotations = Some(keys);
+        });
+    }
+
+    fn render_controller_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        const LAYOUTS: [(ToolbarLayout, &str); 2] = [
+            (ToolbarLayout::Horizontal, "Horizontal"),
+            (ToolbarLayout::Vertical, "Vertical"),
+        ];
+
+        let toolbar = Settings::global(cx).toolbar.clone();
+
+        let layouts = ButtonGroup::new("toolbar-layout")
+            .children(LAYOUTS.iter().map(|(layout, label)| {
+                Button::new(*label)
+                    .label(*label)
+                    .small()
+                    .selected(*layout == toolbar.layout)
+            }))
+            .on_click(|selected: &Vec<usize>, _, cx| {
+                if let Some((layout, _)) = selected.first().and_then(|i| LAYOUTS.get(*i)) {
+                    Settings::update(cx, |settings| settings.toolbar.layout = *layout);
+                }
+            });
+
+        section("Controller")
+            .child(setting_row("Layout", layouts))
+            .child(
+                Checkbox::new("toolbar-collapsed")
+                    .label("Show only the chosen tool")
+                    .checked(toolbar.collapsed)
+                    .on_click(|checked, _, cx| {
+                        Settings::update(cx, |settings| settings.toolbar.collapsed = *checked);
+                    }),
+            )
+            .child(
+                Checkbox::new("toolbar-snap-to-edges")
+                    .label("Stick to the edges of the display")
+                    .checked(toolbar.snap_to_edges)
+                    .on_click(|checked, _, cx| {
+                        Settings::update(cx, |settings| settings.toolbar.snap_to_edges = *checked);
+                    }),
+            )
+    }
+
+    fn render_startup_section(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = Settings::global(cx);
+        let (startup, tray) = (settings.startup.clone(), settings.tray.clone());
+
+        section("Startup")
+            .child(
+                Checkbox::new("startup-hidden")
+                    .label("Hide the controller window")
+                    .checked(startup.hidden)
+                    .on_click(|checked, _, cx| {
+                        Settings::update(cx, |settings| settings.startup.hidden = *checked);
+                    }),
+            )
+            .child(
+                Checkbox::new("startup-restore-position")
+                    .label("Open the controller where it was moved last")
+                    .checked(startup.restore_position)
+                    .on_click(|checked, _, cx| {
+                        Settings::update(cx, |settings| {
+                            settings.startup.restore_position = *checked;
+                        });
+                    }),
+            )
+            .child(
+                Checkbox::new("tray-enabled")
+                    .label("Put an icon in the tray (after restarting)")
+                    .checked(tray.enabled)
+                    .on_click(|checked, _, cx| {
+                        Settings::update(cx, |settings| settings.tray.enabled = *checked);
+                    }),
+            )
+            .child(
+                Checkbox::new("tray-quit-on-close")
+                    .label("Quit when the controller window is closed")
+                    .checked(tray.quit_on_close)
+                    .on_click(|checked, _, cx| {
+                        Settings::update(cx, |settings| settings.tray.quit_on_close = *checked);
+                    }),
+            )
+    }
+}
+
+impl Render for SettingsView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        // Stop recording the shortcut when something else is clicked.
+        if self.recording_shortcut && !self.focus_handle.is_focused(window) {
+            self.recording_shortcut = false;
+            Shortcuts::update(cx);
+        }
+
+        v_flex()
+            .id("settings-view")
+            .size_full()
+            .p_4()
+            .gap_4()
+            .bg(cx.theme().background)
+            .text_color(cx.theme().foreground)
+            .overflow_y_scroll()
+            .children(self.render_problems(cx))
+            .child(self.render_tools_section(cx))
+            .child(self.render_colors_section(cx))
+            .child(self.render_highlight_section(cx))
+            .child(self.render_ripple_section(cx))
+            .child(self.render_keystroke_section(cx))
+            .child(self.render_displays_section(cx))
+            .child(self.render_shortcuts_section(cx))
+            .child(self.render_controller_section(cx))
+            .child(self.render_startup_section(cx))
+    }
+}
+
+/// Write the keys pressed in the same way as `KeyStroke` is shown, e.g. `Ctrl + Shift + H`.
+/// The shortcut needs a modifier, so that it does not take a key used for typing.
+fn shortcut_keys(keystroke: &Keystroke) -> Option<String> {
+    let pressed = &keystroke.modifiers;
+    let modifiers: Vec<_> = [
+        (pressed.control, Modifier::Control),
+        (pressed.alt, Modifier::Alt),
+        (pressed.shift, Modifier::Shift),
+        (pressed.platform, Modifier::Super),
+    ]
+    .into_iter()
+    .filter_map(|(pressed, modifier)| pressed.then_some(modifier))
+    .collect();
+
+    if modifiers.is_empty() {
+        return None;
+    }
+
+    let key = match keystroke.key.as_str() {
+        key if key.chars().count() == 1 => key.to_uppercase(),
+        key if key.starts_with('f') && key[1..].parse::<u8>().is_ok() => key.to_uppercase(),
+        "space" => "Space".to_owned(),
+        "enter" => "Enter".to_owned(),
+        "tab" => "Tab".to_owned(),
+        "backspace" => "Backspace".to_owned(),
+        "delete" => "Delete".to_owned(),
+        "insert" => "Insert".to_owned(),
+        "home" => "Home".to_owned(),
+        "end" => "End".to_owned(),
+        "pageup" => "PgUp".to_owned(),
+        "pagedown" => "PgDn".to_owned(),
+        "left" => "←".to_owned(),
+        "up" => "↑".to_owned(),
+        "right" => "→".to_owned(),
+        "down" => "↓".to_owned(),
+        _ => return None,
+    };
+
+    Some(KeyStroke { modifiers, key }.to_string())
+}
+
+fn update_highlight(cx: &mut App, f: impl FnOnce(&mut HighlightSettings)) {
+    Settings::update(cx, |settings| f(&mut settings.highlight));
+}
+
+/// Compare the colors with the precision of the settings file.
+fn is_same_color(a: Hsla, b: Hsla) -> bool {
+    let (a, b) = (Rgba::from(a), Rgba::from(b));
+    let eq = |x: f32, y: f32| (x - y).abs() < 1. / 255.;
+
+    eq(a.r, b.r) && eq(a.g, b.g) && eq(a.b, b.b)
+}
+
+/// The preset colors to choose from.
+fn color_swatches(
+    id: &'static str,
+    current: Hsla,
+    cx: &App,
+    set: impl Fn(&mut Settings, Hsla) + Clone + 'static,
+) -> impl IntoElement {
+    let swatches = COLORS.iter().enumerate().map(|(i, color)| {
+        let color = color();
+        let set = set.clone();
+
+        div()
+            .id((id, i))
+            .size(px(20.))
+            .rounded_full()
+            .bg(color)
+            .border_2()
+            .border_color(if is_same_color(color, current) {
+                cx.theme().primary
+            } else {
+                cx.theme().border
+            })
+            .cursor_pointer()
+            .on_click(move |_, _, cx| Settings::update(cx, |settings| set(settings, color)))
+    });
+
+    h_flex().items_center().gap_1().children(swatches)
+}
+
+fn section(title: impl Into<SharedString>) -> gpui::Div {
+    v_flex().w_full().gap_2().child(
+        div()
+            .text_sm()
+            .font_weight(gpui::FontWeight::SEMIBOLD)
+            .child(title.into()),
+    )
+}
+
+fn setting_row(label: impl Into<SharedString>, control: impl IntoElement) -> impl IntoElement {
+    h_flex()
+        .w_full()
+        .h(px(ROW_HEIGHT))
+        .items_center()
+        .justify_between()
+        .child(div().text_sm().child(label.into()))
+        .child(control)
+}
+
+/// The control to decrease or increase a value of the settings.
+/// `step` is called with `-1.` or `1.`.
+fn stepper(
+    id: &'static str,
+    value: String,
+    step: impl Fn(&mut Settings, f32) + Clone + 'static,
+) -> impl IntoElement {
+    let decrease = step.clone();
+
+    h_flex()
+        .items_center()
+        .gap_1()
+        .child(
+            Button::new((id, 0usize))
+                .icon(Icon::empty().path("icons/minus.svg"))
+                .ghost()
+                .small()
+                .on_click(move |_, _, cx| Settings::update(cx, |settings| decrease(settings, -1.))),
+        )
+        .child(div().w(px(48.)).text_center().text_sm().child(value))
+        .child(
+            Button::new((id, 1usize))
+                .icon(Icon::empty().path("icons/plus.svg"))
+                .ghost()
+                .small()
+                .on_click(move |_, _, cx| Settings::update(cx, |settings| step(settings, 1.))),
+        )
+}
@@ -0,0 +1,107 @@
+//! The options shown below the tools while a tool is chosen, see `CanvasTool::render_options`.
+
+use gpui::{App, ReadGlobal, UpdateGlobal, div, img, prelude::*, px};
+use gpui_component::{
+    ActiveTheme, Icon, Selectable, Sizable,
+    button::{Button, ButtonGroup, ButtonVariants},
+    h_flex,
+};
+
+use crate::{
+    canvas::{StampKind, ToolState},
+    sticker::StickerSource,
+};
+
+/// The height of the row of the options.
+pub const OPTION_ROW_HEIGHT: f32 = 40.;
+
+pub fn render_stamp_select(cx: &App) -> impl IntoElement {
+    const STAMPS: [(StampKind, &str); 4] = [
+        (StampKind::Number, "icons/hash.svg"),
+        (StampKind::Check, "icons/check.svg"),
+        (StampKind::Cross, "icons/x.svg"),
+        (StampKind::Arrow, "icons/arrow-down-right.svg"),
+    ];
+
+    let current = ToolState::global(cx).stamp;
+
+    h_flex()
+        .w_full()
+        .h(px(OPTION_ROW_HEIGHT))
+        .justify_center()
+        .items_center()
+        .border_t_1()
+        .border_color(cx.theme().border)
+        .child(
+            ButtonGroup::new("stamp-kinds")
+                .children(STAMPS.iter().map(|(kind, icon_path)| {
+                    Button::new(*icon_path)
+                        .icon(Icon::empty().path(*icon_path))
+                        .ghost()
+                        .small()
+                        .selected(*kind == current)
+                }))
+                .on_click(|selected: &Vec<usize>, _, cx| {
+                    if let Some((kind, _)) = selected.first().and_then(|i| STAMPS.get(*i)) {
+                        ToolState::update_global(cx, |state, _| state.stamp = *kind);
+                    }
+                }),
+        )
+}
+
+pub fn render_sticker_select(cx: &App, stickers: &[StickerSource]) -> impl IntoElement {
+    let current = ToolState::global(cx).sticker.as_ref();
+
+    let thumbnails = stickers.iter().enumerate().map(|(i, source)| {
+        let selected = Some(source) == current;
+        let source = source.clone();
+
+        div()
+            .id(("sticker", i))
+            .flex_none()
+            .size_8()
+            .p_1()
+            .rounded_md()
+            .when(selected, |this| this.bg(cx.theme().foreground.alpha(0.2)))
+            .hover(|this| this.bg(cx.theme().foreground.alpha(0.1)))
+            .child(img(source.image_source()).size_full())
+            .on_click(move |_, _, cx| {
+                ToolState::update_global(cx, |state, _| state.sticker = Some(source.clone()));
+            })
+    });
+
+    h_flex()
+        .w_full()
+        .h(px(OPTION_ROW_HEIGHT))
+        .items_center()
+        .gap_1()
+        .px_2()
+        .border_t_1()
+        .border_color(cx.theme().border)
+        .child(
+            h_flex()
+                .id("sticker-list")
+                .flex_1()
+                .gap_1()
+                .overflow_x_scroll()
+                .children(thumbnails),
+        )
+        .child(
+            Button::new("sticker-folder")
+                .icon(Icon::empty().path("icons/folder-open.svg"))
+                .ghost()
+                .small()
+                .tooltip("Open the folder to add your own PNG or SVG stickers")
+                .on_click(|_, _, cx| {
+                    let dir = StickerSource::user_dir().and_then(|dir| {
+                        std::fs::create_dir_all(&dir)?;
+                        Ok(dir)
+                    });
+
+                    match dir {
+                        Ok(dir) => cx.open_with_system(&dir),
+                        Err(error) => eprintln!("Failed to open the sticker folder: {error:#}"),
+                    }
+                }),
+        )
+}
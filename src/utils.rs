@@ -1,4 +1,4 @@
-use gpui::{Pixels, Point, Size, point, px, size};
+use gpui::{Bounds, Pixels, Point, Size, point, px, size};
 
 #[inline]
 pub fn dpi_size_to_gpui(logical_size: dpi::LogicalSize<u32>) -> Size<Pixels> {
@@ -9,3 +9,26 @@ pub fn dpi_size_to_gpui(logical_size: dpi::LogicalSize<u32>) -> Size<Pixels> {
 pub fn dpi_pos_to_gpui(logical_pos: dpi::LogicalPosition<i32>) -> Point<Pixels> {
     point(px(logical_pos.x as _), px(logical_pos.y as _))
 }
+
+/// Convert the bounds to the logical position and size for `dpi`.
+#[inline]
+pub fn gpui_bounds_to_dpi(
+    bounds: Bounds<Pixels>,
+) -> (dpi::LogicalPosition<i32>, dpi::LogicalSize<u32>) {
+    let origin = dpi::LogicalPosition::new(
+        f32::from(bounds.origin.x) as _,
+        f32::from(bounds.origin.y) as _,
+    );
+    let size = dpi::LogicalSize::new(
+        f32::from(bounds.size.width) as _,
+        f32::from(bounds.size.height) as _,
+    );
+
+    (origin, size)
+}
+
+/// Get the key to identify the display across runs.
+#[inline]
+pub fn display_key(display_id: &display_config::DisplayId) -> String {
+    format!("{display_id:?}")
+}